@@ -1,3 +1,14 @@
 mod redis;
 
-pub use crate::redis::{RedisDatabase, RedisError};
+#[cfg(feature = "mock")]
+pub use crate::redis::InMemoryDatabase;
+#[cfg(feature = "cluster")]
+pub use crate::redis::RedisClusterDatabase;
+#[cfg(feature = "tls")]
+pub use crate::redis::TlsConfig;
+pub use crate::redis::{
+    AsyncRedisDatabase, Compression, DeserializeErrorPolicy, InvalidationListener, JsonSerializer,
+    LockGuard, Metrics, Pipeline, PrettyJsonSerializer, ReadReplicaDatabase, RedisDatabase,
+    RedisDatabaseBuilder, RedisError, RedisKey, RedisType, RetryPolicy, Serializer, Subscription,
+    Transaction, TtlStatus,
+};
@@ -1,7 +1,54 @@
+mod async_redis_database;
 mod commands;
+mod compression;
+mod connection_manager;
+mod deserialize_error_policy;
 mod error;
 mod execute_command;
+#[cfg(feature = "mock")]
+mod in_memory_database;
+mod invalidation;
+mod lock;
+mod metrics;
+mod pipeline;
+mod read_replica_database;
+#[cfg(feature = "cluster")]
+mod redis_cluster_database;
 mod redis_database;
+mod redis_key;
+mod redis_type;
+mod replicas;
+mod retry_policy;
+mod scan;
+mod serialization;
+mod subscription;
+#[cfg(test)]
+mod test_support;
+#[cfg(feature = "tls")]
+mod tls_config;
+mod transaction;
+mod ttl_status;
 
+pub use async_redis_database::AsyncRedisDatabase;
+pub use compression::Compression;
+pub use deserialize_error_policy::DeserializeErrorPolicy;
 pub use error::RedisError;
-pub use redis_database::RedisDatabase;
+#[cfg(feature = "mock")]
+pub use in_memory_database::InMemoryDatabase;
+pub use invalidation::InvalidationListener;
+pub use lock::LockGuard;
+pub use metrics::Metrics;
+pub use pipeline::Pipeline;
+pub use read_replica_database::ReadReplicaDatabase;
+#[cfg(feature = "cluster")]
+pub use redis_cluster_database::RedisClusterDatabase;
+pub use redis_database::{RedisDatabase, RedisDatabaseBuilder};
+pub use redis_key::RedisKey;
+pub use redis_type::RedisType;
+pub use retry_policy::RetryPolicy;
+pub use serialization::{JsonSerializer, PrettyJsonSerializer, Serializer};
+pub use subscription::Subscription;
+#[cfg(feature = "tls")]
+pub use tls_config::TlsConfig;
+pub use transaction::Transaction;
+pub use ttl_status::TtlStatus;
@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+use lushus_storage::Table;
+use redis::{aio::ConnectionManager, Client};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::redis::{
+    commands::Command,
+    connection_manager::{connection_error, query_error},
+    error::RedisError,
+    redis_database::validate_url,
+    redis_key::RedisKey,
+    serialization::{deserialize_value, serialize_value, JsonSerializer},
+    ttl_status::TtlStatus,
+};
+
+#[derive(Clone)]
+pub struct AsyncRedisDatabase {
+    connection: ConnectionManager,
+    ttl: Duration,
+}
+
+impl AsyncRedisDatabase {
+    pub async fn new(url: &str, ttl: Duration) -> Result<Self, RedisError> {
+        validate_url(url)?;
+        let client = Client::open(url).map_err(RedisError::ConnectionError)?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .map_err(connection_error)?;
+        Ok(Self { connection, ttl })
+    }
+
+    async fn execute_command<T: redis::FromRedisValue>(
+        &self,
+        command: Command,
+    ) -> Result<T, RedisError> {
+        let mut connection = self.connection.clone();
+        let redis_command: redis::Cmd = command.into();
+        let result = redis_command
+            .query_async(&mut connection)
+            .await
+            .map_err(query_error)?;
+        Ok(result)
+    }
+
+    async fn _get<T: DeserializeOwned>(&self, key: String) -> Result<Option<T>, RedisError> {
+        let command = Command::get(key.clone());
+        let data = self.execute_command::<Option<Vec<u8>>>(command).await?;
+        deserialize_value(&JsonSerializer, &key, data)
+    }
+
+    pub async fn get<TableType>(
+        &self,
+        key: &TableType::Key,
+    ) -> Result<Option<TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = key.to_redis_key();
+        self._get(key).await
+    }
+
+    pub async fn exists<TableType>(&self, key: &TableType::Key) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = key.to_redis_key();
+        let command = Command::exists(key);
+        let data = self.execute_command::<bool>(command).await?;
+        Ok(data)
+    }
+
+    pub async fn insert<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+    ) -> Result<Option<TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = key.to_redis_key();
+        let previous = self._get(key.clone()).await?;
+        let value = serialize_value(&JsonSerializer, &key, value)?;
+        let ttl = self.ttl;
+        let command = Command::set(key, value, Some(ttl));
+        self.execute_command::<()>(command).await?;
+        Ok(previous)
+    }
+
+    pub async fn remove<TableType>(
+        &mut self,
+        key: &TableType::Key,
+    ) -> Result<Option<TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = key.to_redis_key();
+        let previous = self._get(key.clone()).await?;
+        let command = Command::delete(key);
+        self.execute_command::<()>(command).await?;
+        Ok(previous)
+    }
+
+    pub async fn ttl<TableType>(&self, key: &TableType::Key) -> Result<TtlStatus, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = key.to_redis_key();
+        let command = Command::ttl(key);
+        let seconds: i64 = self.execute_command(command).await?;
+        Ok(TtlStatus::from_secs(seconds))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use lushus_storage::Table;
+
+    use super::AsyncRedisDatabase;
+    use crate::redis::test_support::{unique_key, url};
+
+    #[derive(Copy, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Foo {
+        bar: u64,
+    }
+
+    struct FooTable {}
+
+    impl Table for FooTable {
+        type Key = String;
+        type OwnedKey = Self::Key;
+        type Value = Foo;
+        type OwnedValue = Self::Value;
+    }
+
+    #[tokio::test]
+    async fn test_insert_inserts_the_key_value() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = AsyncRedisDatabase::new(&url(), ttl)
+            .await
+            .expect("Unable to connect to Redis");
+        let key = unique_key("async_key");
+        let foo = Foo { bar: 42 };
+        redis
+            .insert::<FooTable>(&key, &foo)
+            .await
+            .expect("Failed to insert into Redis");
+        let ret = redis
+            .get::<FooTable>(&key)
+            .await
+            .expect("Failed to get key from Redis");
+        assert_eq!(ret, Some(foo));
+    }
+
+    #[tokio::test]
+    async fn test_remove_returns_the_previous_value() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = AsyncRedisDatabase::new(&url(), ttl)
+            .await
+            .expect("Unable to connect to Redis");
+        let key = unique_key("async_key_remove");
+        let foo = Foo { bar: 42 };
+        redis
+            .insert::<FooTable>(&key, &foo)
+            .await
+            .expect("Failed to insert into Redis");
+        let prev = redis
+            .remove::<FooTable>(&key)
+            .await
+            .expect("Failed to remove from Redis");
+        assert_eq!(prev, Some(foo));
+        let exists = redis
+            .exists::<FooTable>(&key)
+            .await
+            .expect("Failed to check key from Redis");
+        assert!(!exists);
+    }
+}
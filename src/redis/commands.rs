@@ -1,76 +1,886 @@
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use redis::Arg;
+
+#[derive(Clone)]
 pub enum Command {
+    Append {
+        key: String,
+        value: String,
+    },
+    BitCount {
+        key: String,
+    },
+    Copy {
+        from: String,
+        to: String,
+        replace: bool,
+    },
+    DbSize,
+    DecrBy {
+        key: String,
+        by: i64,
+    },
     Delete {
         key: String,
     },
+    DeleteMany {
+        keys: Vec<String>,
+    },
+    Dump {
+        key: String,
+    },
+    Eval {
+        script: String,
+        keys: Vec<String>,
+        args: Vec<String>,
+    },
     Exists {
         key: String,
     },
+    Expire {
+        key: String,
+        ttl: Duration,
+    },
+    FlushDb,
     Get {
         key: String,
     },
-    Set {
+    GetBit {
         key: String,
-        value: String,
+        offset: u64,
+    },
+    GetDel {
+        key: String,
+    },
+    GetEx {
+        key: String,
+        ttl: Option<Duration>,
+    },
+    GetRange {
+        key: String,
+        start: i64,
+        end: i64,
+    },
+    HExpire {
+        key: String,
+        field: String,
+        ttl: Duration,
+    },
+    HGet {
+        key: String,
+        field: String,
+    },
+    HGetAll {
+        key: String,
+    },
+    HScan {
+        key: String,
+        cursor: u64,
+        count: usize,
+    },
+    HSet {
+        key: String,
+        field: String,
+        value: Vec<u8>,
+    },
+    HTtl {
+        key: String,
+        field: String,
+    },
+    IncrBy {
+        key: String,
+        by: i64,
+    },
+    LLen {
+        key: String,
+    },
+    Lock {
+        key: String,
+        token: String,
         ttl: Duration,
     },
+    LPop {
+        key: String,
+    },
+    MemoryUsage {
+        key: String,
+    },
+    MGet {
+        keys: Vec<String>,
+    },
+    ObjectEncoding {
+        key: String,
+    },
+    ObjectIdleTime {
+        key: String,
+    },
+    Persist {
+        key: String,
+    },
+    PExpireAt {
+        key: String,
+        at: SystemTime,
+    },
+    PfAdd {
+        key: String,
+        element: Vec<u8>,
+    },
+    PfCount {
+        key: String,
+    },
+    PfMerge {
+        destination: String,
+        sources: Vec<String>,
+    },
+    Ping,
+    Publish {
+        channel: String,
+        message: String,
+    },
+    PTTL {
+        key: String,
+    },
+    Rename {
+        from: String,
+        to: String,
+    },
+    RenameNx {
+        from: String,
+        to: String,
+    },
+    Restore {
+        key: String,
+        ttl: Duration,
+        payload: Vec<u8>,
+    },
+    RPush {
+        key: String,
+        value: Vec<u8>,
+    },
+    SAdd {
+        key: String,
+        member: Vec<u8>,
+    },
+    Scan {
+        cursor: u64,
+        pattern: String,
+        count: usize,
+    },
+    Set {
+        key: String,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+    },
+    SetBit {
+        key: String,
+        offset: u64,
+        value: bool,
+    },
+    SetGet {
+        key: String,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+    },
+    SetNx {
+        key: String,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+    },
+    SetRange {
+        key: String,
+        offset: u64,
+        value: Vec<u8>,
+    },
+    SIsMember {
+        key: String,
+        member: Vec<u8>,
+    },
+    SMembers {
+        key: String,
+    },
+    SRem {
+        key: String,
+        member: Vec<u8>,
+    },
+    StrLen {
+        key: String,
+    },
+    Time,
+    Touch {
+        key: String,
+    },
     TTL {
         key: String,
     },
+    Type {
+        key: String,
+    },
+    Unwatch,
     Update {
         key: String,
-        value: String,
-        ttl: Duration,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+    },
+    Wait {
+        replicas: usize,
+        timeout: Duration,
+    },
+    Watch {
+        keys: Vec<String>,
+    },
+    ZAdd {
+        key: String,
+        member: Vec<u8>,
+        score: f64,
+    },
+    ZRange {
+        key: String,
+        start: isize,
+        stop: isize,
+    },
+    ZRank {
+        key: String,
+        member: Vec<u8>,
     },
 }
 
 impl Command {
+    pub fn append(key: String, value: String) -> Self {
+        Self::Append { key, value }
+    }
+    pub fn bit_count(key: String) -> Self {
+        Self::BitCount { key }
+    }
+    pub fn copy(from: String, to: String, replace: bool) -> Self {
+        Self::Copy { from, to, replace }
+    }
+    pub fn db_size() -> Self {
+        Self::DbSize
+    }
+    pub fn decr_by(key: String, by: i64) -> Self {
+        Self::DecrBy { key, by }
+    }
     pub fn delete(key: String) -> Self {
         Self::Delete { key }
     }
+    pub fn delete_many(keys: Vec<String>) -> Self {
+        Self::DeleteMany { keys }
+    }
+    pub fn dump(key: String) -> Self {
+        Self::Dump { key }
+    }
+    pub fn eval(script: String, keys: Vec<String>, args: Vec<String>) -> Self {
+        Self::Eval { script, keys, args }
+    }
     pub fn exists(key: String) -> Self {
         Self::Exists { key }
     }
+    pub fn expire(key: String, ttl: Duration) -> Self {
+        Self::Expire { key, ttl }
+    }
+    pub fn flush_db() -> Self {
+        Self::FlushDb
+    }
     pub fn get(key: String) -> Self {
         Self::Get { key }
     }
-    pub fn set(key: String, value: String, ttl: Duration) -> Self {
+    pub fn get_bit(key: String, offset: u64) -> Self {
+        Self::GetBit { key, offset }
+    }
+    pub fn get_del(key: String) -> Self {
+        Self::GetDel { key }
+    }
+    pub fn get_ex(key: String, ttl: Option<Duration>) -> Self {
+        Self::GetEx { key, ttl }
+    }
+    pub fn get_range(key: String, start: i64, end: i64) -> Self {
+        Self::GetRange { key, start, end }
+    }
+    pub fn hexpire(key: String, field: String, ttl: Duration) -> Self {
+        Self::HExpire { key, field, ttl }
+    }
+    pub fn hget(key: String, field: String) -> Self {
+        Self::HGet { key, field }
+    }
+    pub fn hget_all(key: String) -> Self {
+        Self::HGetAll { key }
+    }
+    pub fn hscan(key: String, cursor: u64, count: usize) -> Self {
+        Self::HScan { key, cursor, count }
+    }
+    pub fn hset(key: String, field: String, value: Vec<u8>) -> Self {
+        Self::HSet { key, field, value }
+    }
+    pub fn httl(key: String, field: String) -> Self {
+        Self::HTtl { key, field }
+    }
+    pub fn incr_by(key: String, by: i64) -> Self {
+        Self::IncrBy { key, by }
+    }
+    pub fn llen(key: String) -> Self {
+        Self::LLen { key }
+    }
+    pub fn lock(key: String, token: String, ttl: Duration) -> Self {
+        Self::Lock { key, token, ttl }
+    }
+    pub fn lpop(key: String) -> Self {
+        Self::LPop { key }
+    }
+    pub fn memory_usage(key: String) -> Self {
+        Self::MemoryUsage { key }
+    }
+    pub fn mget(keys: Vec<String>) -> Self {
+        Self::MGet { keys }
+    }
+    pub fn object_encoding(key: String) -> Self {
+        Self::ObjectEncoding { key }
+    }
+    pub fn object_idle_time(key: String) -> Self {
+        Self::ObjectIdleTime { key }
+    }
+    pub fn persist(key: String) -> Self {
+        Self::Persist { key }
+    }
+    pub fn pexpire_at(key: String, at: SystemTime) -> Self {
+        Self::PExpireAt { key, at }
+    }
+    pub fn pfadd(key: String, element: Vec<u8>) -> Self {
+        Self::PfAdd { key, element }
+    }
+    pub fn pfcount(key: String) -> Self {
+        Self::PfCount { key }
+    }
+    pub fn pfmerge(destination: String, sources: Vec<String>) -> Self {
+        Self::PfMerge {
+            destination,
+            sources,
+        }
+    }
+    pub fn ping() -> Self {
+        Self::Ping
+    }
+    pub fn publish(channel: String, message: String) -> Self {
+        Self::Publish { channel, message }
+    }
+    pub fn pttl(key: String) -> Self {
+        Self::PTTL { key }
+    }
+    pub fn rename(from: String, to: String) -> Self {
+        Self::Rename { from, to }
+    }
+    pub fn rename_nx(from: String, to: String) -> Self {
+        Self::RenameNx { from, to }
+    }
+    pub fn restore(key: String, ttl: Duration, payload: Vec<u8>) -> Self {
+        Self::Restore { key, ttl, payload }
+    }
+    pub fn rpush(key: String, value: Vec<u8>) -> Self {
+        Self::RPush { key, value }
+    }
+    pub fn sadd(key: String, member: Vec<u8>) -> Self {
+        Self::SAdd { key, member }
+    }
+    pub fn scan(cursor: u64, pattern: String, count: usize) -> Self {
+        Self::Scan {
+            cursor,
+            pattern,
+            count,
+        }
+    }
+    pub fn set(key: String, value: Vec<u8>, ttl: Option<Duration>) -> Self {
         Self::Set { key, value, ttl }
     }
+    pub fn set_bit(key: String, offset: u64, value: bool) -> Self {
+        Self::SetBit { key, offset, value }
+    }
+    pub fn set_get(key: String, value: Vec<u8>, ttl: Option<Duration>) -> Self {
+        Self::SetGet { key, value, ttl }
+    }
+    pub fn set_nx(key: String, value: Vec<u8>, ttl: Option<Duration>) -> Self {
+        Self::SetNx { key, value, ttl }
+    }
+    pub fn set_range(key: String, offset: u64, value: Vec<u8>) -> Self {
+        Self::SetRange { key, offset, value }
+    }
+    pub fn sismember(key: String, member: Vec<u8>) -> Self {
+        Self::SIsMember { key, member }
+    }
+    pub fn smembers(key: String) -> Self {
+        Self::SMembers { key }
+    }
+    pub fn srem(key: String, member: Vec<u8>) -> Self {
+        Self::SRem { key, member }
+    }
+    pub fn strlen(key: String) -> Self {
+        Self::StrLen { key }
+    }
+    pub fn time() -> Self {
+        Self::Time
+    }
+    pub fn touch(key: String) -> Self {
+        Self::Touch { key }
+    }
     pub fn ttl(key: String) -> Self {
         Self::TTL { key }
     }
-    pub fn update(key: String, value: String, ttl: Duration) -> Self {
+    pub fn key_type(key: String) -> Self {
+        Self::Type { key }
+    }
+    pub fn unwatch() -> Self {
+        Self::Unwatch
+    }
+    pub fn update(key: String, value: Vec<u8>, ttl: Option<Duration>) -> Self {
         Self::Update { key, value, ttl }
     }
+    pub fn wait(replicas: usize, timeout: Duration) -> Self {
+        Self::Wait { replicas, timeout }
+    }
+    pub fn watch(keys: Vec<String>) -> Self {
+        Self::Watch { keys }
+    }
+    pub fn zadd(key: String, member: Vec<u8>, score: f64) -> Self {
+        Self::ZAdd { key, member, score }
+    }
+    pub fn zrange(key: String, start: isize, stop: isize) -> Self {
+        Self::ZRange { key, start, stop }
+    }
+    pub fn zrank(key: String, member: Vec<u8>) -> Self {
+        Self::ZRank { key, member }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Append { .. } => "APPEND",
+            Command::BitCount { .. } => "BITCOUNT",
+            Command::Copy { .. } => "COPY",
+            Command::DbSize => "DBSIZE",
+            Command::DecrBy { .. } => "DECRBY",
+            Command::Delete { .. } => "DEL",
+            Command::DeleteMany { .. } => "DEL",
+            Command::Dump { .. } => "DUMP",
+            Command::Eval { .. } => "EVAL",
+            Command::Exists { .. } => "EXISTS",
+            Command::Expire { .. } => "PEXPIRE",
+            Command::FlushDb => "FLUSHDB",
+            Command::Get { .. } => "GET",
+            Command::GetBit { .. } => "GETBIT",
+            Command::GetDel { .. } => "GETDEL",
+            Command::GetEx { .. } => "GETEX",
+            Command::GetRange { .. } => "GETRANGE",
+            Command::HExpire { .. } => "HPEXPIRE",
+            Command::HGet { .. } => "HGET",
+            Command::HGetAll { .. } => "HGETALL",
+            Command::HScan { .. } => "HSCAN",
+            Command::HSet { .. } => "HSET",
+            Command::HTtl { .. } => "HTTL",
+            Command::IncrBy { .. } => "INCRBY",
+            Command::LLen { .. } => "LLEN",
+            Command::Lock { .. } => "SET",
+            Command::LPop { .. } => "LPOP",
+            Command::MemoryUsage { .. } => "MEMORY",
+            Command::MGet { .. } => "MGET",
+            Command::ObjectEncoding { .. } => "OBJECT",
+            Command::ObjectIdleTime { .. } => "OBJECT",
+            Command::Persist { .. } => "PERSIST",
+            Command::PExpireAt { .. } => "PEXPIREAT",
+            Command::PfAdd { .. } => "PFADD",
+            Command::PfCount { .. } => "PFCOUNT",
+            Command::PfMerge { .. } => "PFMERGE",
+            Command::Ping => "PING",
+            Command::Publish { .. } => "PUBLISH",
+            Command::PTTL { .. } => "PTTL",
+            Command::Rename { .. } => "RENAME",
+            Command::RenameNx { .. } => "RENAMENX",
+            Command::Restore { .. } => "RESTORE",
+            Command::RPush { .. } => "RPUSH",
+            Command::SAdd { .. } => "SADD",
+            Command::Scan { .. } => "SCAN",
+            Command::Set { .. } => "SET",
+            Command::SetBit { .. } => "SETBIT",
+            Command::SetGet { .. } => "SET",
+            Command::SetNx { .. } => "SET",
+            Command::SetRange { .. } => "SETRANGE",
+            Command::SIsMember { .. } => "SISMEMBER",
+            Command::SMembers { .. } => "SMEMBERS",
+            Command::SRem { .. } => "SREM",
+            Command::StrLen { .. } => "STRLEN",
+            Command::Time => "TIME",
+            Command::Touch { .. } => "TOUCH",
+            Command::TTL { .. } => "TTL",
+            Command::Type { .. } => "TYPE",
+            Command::Unwatch => "UNWATCH",
+            Command::Update { .. } => "SET",
+            Command::Wait { .. } => "WAIT",
+            Command::Watch { .. } => "WATCH",
+            Command::ZAdd { .. } => "ZADD",
+            Command::ZRange { .. } => "ZRANGE",
+            Command::ZRank { .. } => "ZRANK",
+        }
+    }
+
+    /// `None` for commands without a key or with several.
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            Command::Append { key, .. }
+            | Command::BitCount { key }
+            | Command::Copy { from: key, .. }
+            | Command::DecrBy { key, .. }
+            | Command::Delete { key }
+            | Command::Dump { key }
+            | Command::Exists { key }
+            | Command::Expire { key, .. }
+            | Command::Get { key }
+            | Command::GetBit { key, .. }
+            | Command::GetDel { key }
+            | Command::GetEx { key, .. }
+            | Command::GetRange { key, .. }
+            | Command::HExpire { key, .. }
+            | Command::HGet { key, .. }
+            | Command::HGetAll { key }
+            | Command::HScan { key, .. }
+            | Command::HSet { key, .. }
+            | Command::HTtl { key, .. }
+            | Command::IncrBy { key, .. }
+            | Command::LLen { key }
+            | Command::Lock { key, .. }
+            | Command::LPop { key }
+            | Command::MemoryUsage { key }
+            | Command::ObjectEncoding { key }
+            | Command::ObjectIdleTime { key }
+            | Command::Persist { key }
+            | Command::PExpireAt { key, .. }
+            | Command::PfAdd { key, .. }
+            | Command::PfCount { key }
+            | Command::PfMerge {
+                destination: key, ..
+            }
+            | Command::PTTL { key }
+            | Command::Rename { from: key, .. }
+            | Command::RenameNx { from: key, .. }
+            | Command::Restore { key, .. }
+            | Command::RPush { key, .. }
+            | Command::SAdd { key, .. }
+            | Command::Set { key, .. }
+            | Command::SetBit { key, .. }
+            | Command::SetGet { key, .. }
+            | Command::SetNx { key, .. }
+            | Command::SetRange { key, .. }
+            | Command::SIsMember { key, .. }
+            | Command::SMembers { key }
+            | Command::SRem { key, .. }
+            | Command::StrLen { key }
+            | Command::Touch { key }
+            | Command::TTL { key }
+            | Command::Type { key }
+            | Command::Update { key, .. }
+            | Command::ZAdd { key, .. }
+            | Command::ZRange { key, .. }
+            | Command::ZRank { key, .. } => Some(key),
+            Command::DbSize
+            | Command::DeleteMany { .. }
+            | Command::Eval { .. }
+            | Command::FlushDb
+            | Command::MGet { .. }
+            | Command::Ping
+            | Command::Publish { .. }
+            | Command::Scan { .. }
+            | Command::Time
+            | Command::Unwatch
+            | Command::Wait { .. }
+            | Command::Watch { .. } => None,
+        }
+    }
+
+    /// The command as it would be typed into `redis-cli`, for reproducing it by hand.
+    pub fn to_cli_string(&self) -> String {
+        cli_string(&self.into())
+    }
+}
+
+/// Renders each argument as `redis-cli` reads it, quoting any that are empty or contain
+/// whitespace, quotes or bytes outside printable ASCII.
+pub fn cli_string(command: &redis::Cmd) -> String {
+    command
+        .args_iter()
+        .map(|arg| match arg {
+            Arg::Simple(arg) => cli_arg(arg),
+            Arg::Cursor => "0".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn cli_arg(arg: &[u8]) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .iter()
+            .all(|b| b.is_ascii_graphic() && !matches!(b, b'"' | b'\'' | b'\\'));
+    if plain {
+        return String::from_utf8_lossy(arg).into_owned();
+    }
+    let mut quoted = String::from("\"");
+    for &b in arg {
+        match b {
+            b'"' => quoted.push_str("\\\""),
+            b'\\' => quoted.push_str("\\\\"),
+            b'\n' => quoted.push_str("\\n"),
+            b'\r' => quoted.push_str("\\r"),
+            b'\t' => quoted.push_str("\\t"),
+            b if b == b' ' || b.is_ascii_graphic() => quoted.push(char::from(b)),
+            b => quoted.push_str(&format!("\\x{b:02x}")),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The `PX` argument of a `SET` or `GETEX`, omitted when the key should not expire.
+fn expiry(ttl: Option<Duration>) -> Vec<String> {
+    ttl.map(|ttl| vec!["PX".to_string(), millis(ttl).to_string()])
+        .unwrap_or_default()
+}
+
+/// Sent in milliseconds so sub-second TTLs are not truncated to zero, which would
+/// delete the key.
+fn millis(ttl: Duration) -> u64 {
+    u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Instants before the epoch map to zero, which Redis treats as already expired.
+fn unix_millis(at: SystemTime) -> u64 {
+    let millis = at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    u64::try_from(millis).unwrap_or(u64::MAX)
 }
 
 impl From<Command> for redis::Cmd {
     fn from(command: Command) -> Self {
+        (&command).into()
+    }
+}
+
+impl From<&Command> for redis::Cmd {
+    fn from(command: &Command) -> Self {
         match command {
+            Command::Append { key, value } => redis::cmd("APPEND").arg(&[&key, &value]).clone(),
+            Command::BitCount { key } => redis::cmd("BITCOUNT").arg(&[&key]).clone(),
+            Command::Copy { from, to, replace } => {
+                let mut command = redis::cmd("COPY");
+                command.arg(&[&from, &to]);
+                if *replace {
+                    command.arg("REPLACE");
+                }
+                command
+            }
+            Command::DbSize => redis::cmd("DBSIZE"),
+            Command::DecrBy { key, by } => redis::cmd("DECRBY").arg(key).arg(by).clone(),
             Command::Delete { key } => redis::cmd("DEL").arg(&[&key]).clone(),
+            Command::DeleteMany { keys } => redis::cmd("DEL").arg(keys).clone(),
+            Command::Dump { key } => redis::cmd("DUMP").arg(&[&key]).clone(),
+            Command::Eval { script, keys, args } => redis::cmd("EVAL")
+                .arg(script)
+                .arg(keys.len())
+                .arg(keys)
+                .arg(args)
+                .clone(),
             Command::Exists { key } => redis::cmd("EXISTS").arg(&[&key]).clone(),
+            Command::Expire { key, ttl } => {
+                redis::cmd("PEXPIRE").arg(key).arg(millis(*ttl)).clone()
+            }
+            Command::FlushDb => redis::cmd("FLUSHDB").clone(),
             Command::Get { key } => redis::cmd("GET").arg(&[&key]).clone(),
+            Command::GetBit { key, offset } => redis::cmd("GETBIT").arg(key).arg(offset).clone(),
+            Command::GetDel { key } => redis::cmd("GETDEL").arg(key).clone(),
+            Command::GetEx { key, ttl } => redis::cmd("GETEX").arg(key).arg(expiry(*ttl)).clone(),
+            Command::GetRange { key, start, end } => {
+                redis::cmd("GETRANGE").arg(key).arg(start).arg(end).clone()
+            }
+            Command::HExpire { key, field, ttl } => redis::cmd("HPEXPIRE")
+                .arg(key)
+                .arg(millis(*ttl))
+                .arg("FIELDS")
+                .arg(1)
+                .arg(field)
+                .clone(),
+            Command::HGet { key, field } => redis::cmd("HGET").arg(&[&key, &field]).clone(),
+            Command::HGetAll { key } => redis::cmd("HGETALL").arg(&[&key]).clone(),
+            Command::HScan { key, cursor, count } => redis::cmd("HSCAN")
+                .arg(key)
+                .arg(cursor)
+                .arg("COUNT")
+                .arg(count)
+                .clone(),
+            Command::HSet { key, field, value } => {
+                redis::cmd("HSET").arg(&[&key, &field]).arg(value).clone()
+            }
+            Command::HTtl { key, field } => redis::cmd("HTTL")
+                .arg(key)
+                .arg("FIELDS")
+                .arg(1)
+                .arg(field)
+                .clone(),
+            Command::IncrBy { key, by } => redis::cmd("INCRBY").arg(key).arg(by).clone(),
+            Command::LLen { key } => redis::cmd("LLEN").arg(&[&key]).clone(),
+            Command::Lock { key, token, ttl } => redis::cmd("SET")
+                .arg(key)
+                .arg(token)
+                .arg(&["NX", "PX", format!("{}", ttl.as_millis()).as_ref()])
+                .clone(),
+            Command::LPop { key } => redis::cmd("LPOP").arg(&[&key]).clone(),
+            Command::MemoryUsage { key } => redis::cmd("MEMORY").arg(&["USAGE", key]).clone(),
+            Command::MGet { keys } => redis::cmd("MGET").arg(keys).clone(),
+            Command::ObjectEncoding { key } => redis::cmd("OBJECT").arg(&["ENCODING", key]).clone(),
+            Command::ObjectIdleTime { key } => redis::cmd("OBJECT").arg(&["IDLETIME", key]).clone(),
+            Command::Persist { key } => redis::cmd("PERSIST").arg(&[&key]).clone(),
+            Command::PExpireAt { key, at } => redis::cmd("PEXPIREAT")
+                .arg(key)
+                .arg(unix_millis(*at))
+                .clone(),
+            Command::PfAdd { key, element } => redis::cmd("PFADD").arg(key).arg(element).clone(),
+            Command::PfCount { key } => redis::cmd("PFCOUNT").arg(&[&key]).clone(),
+            Command::PfMerge {
+                destination,
+                sources,
+            } => redis::cmd("PFMERGE").arg(destination).arg(sources).clone(),
+            Command::Ping => redis::cmd("PING"),
+            Command::Publish { channel, message } => {
+                redis::cmd("PUBLISH").arg(&[&channel, &message]).clone()
+            }
+            Command::PTTL { key } => redis::cmd("PTTL").arg(&[&key]).clone(),
+            Command::Rename { from, to } => redis::cmd("RENAME").arg(&[&from, &to]).clone(),
+            Command::RenameNx { from, to } => redis::cmd("RENAMENX").arg(&[&from, &to]).clone(),
+            Command::Restore { key, ttl, payload } => redis::cmd("RESTORE")
+                .arg(key)
+                .arg(u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX))
+                .arg(payload)
+                .clone(),
+            Command::RPush { key, value } => redis::cmd("RPUSH").arg(key).arg(value).clone(),
+            Command::SAdd { key, member } => redis::cmd("SADD").arg(key).arg(member).clone(),
+            Command::Scan {
+                cursor,
+                pattern,
+                count,
+            } => redis::cmd("SCAN")
+                .arg(cursor)
+                .arg(&["MATCH", pattern])
+                .arg(&["COUNT", &count.to_string()])
+                .clone(),
             Command::Set { key, value, ttl } => redis::cmd("SET")
-                .arg(&[
-                    &key,
-                    &value,
-                    "NX",
-                    "EX",
-                    format!("{}", ttl.as_secs()).as_ref(),
-                ])
+                .arg(key)
+                .arg(value)
+                .arg(expiry(*ttl))
+                .clone(),
+            Command::SetBit { key, offset, value } => redis::cmd("SETBIT")
+                .arg(key)
+                .arg(offset)
+                .arg(i32::from(*value))
                 .clone(),
+            Command::SetGet { key, value, ttl } => redis::cmd("SET")
+                .arg(key)
+                .arg(value)
+                .arg(expiry(*ttl))
+                .arg("GET")
+                .clone(),
+            Command::SetNx { key, value, ttl } => redis::cmd("SET")
+                .arg(key)
+                .arg(value)
+                .arg("NX")
+                .arg(expiry(*ttl))
+                .clone(),
+            Command::SetRange { key, offset, value } => redis::cmd("SETRANGE")
+                .arg(key)
+                .arg(offset)
+                .arg(value)
+                .clone(),
+            Command::SIsMember { key, member } => {
+                redis::cmd("SISMEMBER").arg(key).arg(member).clone()
+            }
+            Command::SMembers { key } => redis::cmd("SMEMBERS").arg(&[&key]).clone(),
+            Command::SRem { key, member } => redis::cmd("SREM").arg(key).arg(member).clone(),
+            Command::StrLen { key } => redis::cmd("STRLEN").arg(key).clone(),
+            Command::Time => redis::cmd("TIME"),
+            Command::Touch { key } => redis::cmd("TOUCH").arg(&[&key]).clone(),
             Command::TTL { key } => redis::cmd("TTL").arg(&[&key]).clone(),
+            Command::Type { key } => redis::cmd("TYPE").arg(&[&key]).clone(),
+            Command::Unwatch => redis::cmd("UNWATCH"),
             Command::Update { key, value, ttl } => redis::cmd("SET")
-                .arg(&[
-                    &key,
-                    &value,
-                    "XX",
-                    "EX",
-                    format!("{}", ttl.as_secs()).as_ref(),
-                ])
+                .arg(key)
+                .arg(value)
+                .arg("XX")
+                .arg(expiry(*ttl))
+                .clone(),
+            Command::Wait { replicas, timeout } => redis::cmd("WAIT")
+                .arg(replicas)
+                .arg(u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX))
                 .clone(),
+            Command::Watch { keys } => redis::cmd("WATCH").arg(keys).clone(),
+            Command::ZAdd { key, member, score } => {
+                redis::cmd("ZADD").arg(key).arg(score).arg(member).clone()
+            }
+            Command::ZRange { key, start, stop } => redis::cmd("ZRANGE")
+                .arg(key)
+                .arg(start)
+                .arg(stop)
+                .arg("WITHSCORES")
+                .clone(),
+            Command::ZRank { key, member } => redis::cmd("ZRANK").arg(key).arg(member).clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Command;
+
+    #[test]
+    fn test_to_cli_string_leaves_plain_arguments_unquoted() {
+        let command = Command::set(
+            "key".to_string(),
+            b"42".to_vec(),
+            Some(Duration::from_secs(5)),
+        );
+        assert_eq!(command.to_cli_string(), "SET key 42 PX 5000");
+    }
+
+    #[test]
+    fn test_to_cli_string_quotes_json_and_binary_values() {
+        let command = Command::append("key".to_string(), "{\"a b\": 1}".to_string());
+        assert_eq!(command.to_cli_string(), r#"APPEND key "{\"a b\": 1}""#);
+        let command = Command::set("key".to_string(), vec![0x00, 0xff, b'\n'], None);
+        assert_eq!(command.to_cli_string(), r#"SET key "\x00\xff\n""#);
+    }
+
+    #[test]
+    fn test_set_keeps_sub_second_ttls() {
+        let ttl = Some(Duration::from_millis(500));
+        let command = Command::set("key".to_string(), b"42".to_vec(), ttl);
+        assert_eq!(command.to_cli_string(), "SET key 42 PX 500");
+        let command = Command::get_ex("key".to_string(), ttl);
+        assert_eq!(command.to_cli_string(), "GETEX key PX 500");
+    }
+
+    #[test]
+    fn test_expire_keeps_sub_second_ttls() {
+        let command = Command::expire("key".to_string(), Duration::from_millis(500));
+        assert_eq!(command.to_cli_string(), "PEXPIRE key 500");
+    }
+
+    #[test]
+    fn test_hexpire_keeps_sub_second_ttls() {
+        let ttl = Duration::from_millis(500);
+        let command = Command::hexpire("key".to_string(), "field".to_string(), ttl);
+        assert_eq!(command.to_cli_string(), "HPEXPIRE key 500 FIELDS 1 field");
+    }
+
+    #[test]
+    fn test_borrowed_commands_build_the_same_request() {
+        let command = Command::copy("from".to_string(), "to".to_string(), true);
+        let borrowed = redis::Cmd::from(&command).get_packed_command();
+        assert_eq!(borrowed, redis::Cmd::from(command).get_packed_command());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::redis::error::RedisError;
+
+// Compressed values start with `MAGIC` followed by the algorithm. 0xFF never occurs in
+// UTF-8, and the marker is only looked for when compression is configured, so values
+// from any serializer written without compression read back as they are.
+const MAGIC: &[u8] = b"\xffLRZ";
+const GZIP_HEADER: u8 = b'g';
+const ZSTD_HEADER: u8 = b'z';
+const HEADER_LEN: usize = MAGIC.len() + 1;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+    #[cfg(feature = "gzip")]
+    Gzip,
+    #[cfg(feature = "zstd")]
+    Zstd { level: i32 },
+}
+
+impl Compression {
+    /// Values that would not shrink are stored uncompressed.
+    pub fn compress(&self, key: &str, data: Vec<u8>) -> Result<Vec<u8>, RedisError> {
+        let compressed = self
+            .encode(&data)
+            .map_err(|e| RedisError::SerializeError(key.to_string(), e.to_string()))?;
+        match compressed {
+            Some((header, compressed)) if compressed.len() + HEADER_LEN < data.len() => {
+                let mut value = Vec::with_capacity(compressed.len() + HEADER_LEN);
+                value.extend(MAGIC);
+                value.push(header);
+                value.extend(compressed);
+                Ok(value)
+            }
+            _ => Ok(data),
+        }
+    }
+
+    #[cfg_attr(not(any(feature = "gzip", feature = "zstd")), allow(unused_variables))]
+    fn encode(&self, data: &[u8]) -> std::io::Result<Option<(u8, Vec<u8>)>> {
+        match self {
+            Compression::None => Ok(None),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => gzip_compress(data).map(|c| Some((GZIP_HEADER, c))),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd { level } => {
+                zstd::encode_all(data, *level).map(|c| Some((ZSTD_HEADER, c)))
+            }
+        }
+    }
+
+    /// Values written before compression was enabled are returned unchanged. With
+    /// `Compression::None` nothing is decompressed, whatever the value starts with.
+    pub fn decompress(&self, key: &str, data: Vec<u8>) -> Result<Vec<u8>, RedisError> {
+        if matches!(self, Compression::None) {
+            return Ok(data);
+        }
+        let Some(rest) = data.strip_prefix(MAGIC) else {
+            return Ok(data);
+        };
+        let decompressed = match rest.split_first() {
+            Some((&GZIP_HEADER, compressed)) => gzip_decompress(compressed),
+            Some((&ZSTD_HEADER, compressed)) => zstd_decompress(compressed),
+            _ => return Ok(data),
+        };
+        decompressed.map_err(|e| RedisError::DeserializeError(key.to_string(), e))
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gzip_compress(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+#[cfg(feature = "gzip")]
+fn gzip_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+
+    let mut decompressed = Vec::new();
+    flate2::read::GzDecoder::new(data)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| e.to_string())?;
+    Ok(decompressed)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip_decompress(_data: &[u8]) -> Result<Vec<u8>, String> {
+    Err("value is gzip compressed but the gzip feature is disabled".to_string())
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    zstd::decode_all(data).map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decompress(_data: &[u8]) -> Result<Vec<u8>, String> {
+    Err("value is zstd compressed but the zstd feature is disabled".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Compression, MAGIC};
+
+    #[test]
+    fn test_decompress_passes_through_uncompressed_values() {
+        let data = br#"{"bar":42}"#.to_vec();
+        let ret = Compression::None
+            .decompress("key", data.clone())
+            .expect("Failed to decompress");
+        assert_eq!(ret, data);
+    }
+
+    #[test]
+    fn test_none_never_decompresses() {
+        let mut data = MAGIC.to_vec();
+        data.extend(b"g\x01\x02");
+        let ret = Compression::None
+            .decompress("key", data.clone())
+            .expect("Failed to decompress");
+        assert_eq!(ret, data);
+    }
+
+    #[test]
+    fn test_none_stores_the_value_unchanged() {
+        let data = br#"{"bar":42}"#.to_vec();
+        let ret = Compression::None
+            .compress("key", data.clone())
+            .expect("Failed to compress");
+        assert_eq!(ret, data);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_round_trips_the_value() {
+        let data = br#"{"bar":42}"#.repeat(100);
+        let compressed = Compression::Gzip
+            .compress("key", data.clone())
+            .expect("Failed to compress");
+        assert!(compressed.len() < data.len());
+        let ret = Compression::Gzip
+            .decompress("key", compressed)
+            .expect("Failed to decompress");
+        assert_eq!(ret, data);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_passes_through_values_that_look_like_the_old_header() {
+        let data = vec![0x01, 0x2a, 0x00];
+        let ret = Compression::Gzip
+            .decompress("key", data.clone())
+            .expect("Failed to decompress");
+        assert_eq!(ret, data);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_round_trips_the_value() {
+        let data = br#"{"bar":42}"#.repeat(100);
+        let compressed = Compression::Zstd { level: 3 }
+            .compress("key", data.clone())
+            .expect("Failed to compress");
+        assert!(compressed.len() < data.len());
+        let ret = Compression::Zstd { level: 3 }
+            .decompress("key", compressed)
+            .expect("Failed to decompress");
+        assert_eq!(ret, data);
+    }
+}
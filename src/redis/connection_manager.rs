@@ -0,0 +1,370 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use r2d2::Pool;
+#[cfg(feature = "sentinel")]
+use redis::{sentinel::Sentinel, FromRedisValue};
+use redis::{Client, Connection, ConnectionAddr, ConnectionLike, ErrorKind, RedisResult, Value};
+
+use crate::redis::error::RedisError;
+
+/// TCP connections are opened with `TCP_NODELAY`, so small commands are not held back
+/// by Nagle's algorithm, and with TCP keepalive at the system's default intervals, which
+/// `redis` enables by default.
+#[derive(Clone, Debug)]
+pub struct RedisConnectionManager {
+    endpoint: Endpoint,
+    connect_timeout: Option<Duration>,
+    command_timeout: Option<Duration>,
+    db: Option<i64>,
+}
+
+#[derive(Clone)]
+enum Endpoint {
+    Client(Client),
+    #[cfg(feature = "sentinel")]
+    Sentinel {
+        sentinel: Arc<Mutex<Sentinel>>,
+        service_name: String,
+    },
+}
+
+impl fmt::Debug for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endpoint::Client(client) => f.debug_tuple("Client").field(client).finish(),
+            #[cfg(feature = "sentinel")]
+            Endpoint::Sentinel { service_name, .. } => f
+                .debug_struct("Sentinel")
+                .field("service_name", service_name)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+impl RedisConnectionManager {
+    pub fn new(client: Client, connect_timeout: Option<Duration>) -> Self {
+        Self {
+            endpoint: Endpoint::Client(client),
+            connect_timeout,
+            command_timeout: None,
+            db: None,
+        }
+    }
+
+    /// Resolves the current master through Sentinel every time a connection is opened.
+    #[cfg(feature = "sentinel")]
+    pub fn sentinel(
+        sentinel: Sentinel,
+        service_name: String,
+        connect_timeout: Option<Duration>,
+    ) -> Self {
+        Self {
+            endpoint: Endpoint::Sentinel {
+                sentinel: Arc::new(Mutex::new(sentinel)),
+                service_name,
+            },
+            connect_timeout,
+            command_timeout: None,
+            db: None,
+        }
+    }
+
+    /// Every new connection switches to `db` with `SELECT`, overriding any database
+    /// number in the URL.
+    pub fn with_db(self, db: i64) -> Self {
+        Self {
+            db: Some(db),
+            ..self
+        }
+    }
+
+    /// Bounds how long each connection waits to send a command or read its reply, after
+    /// which the command fails with `RedisError::TimeoutError`. The timeout must not be
+    /// zero.
+    pub fn with_command_timeout(self, command_timeout: Duration) -> Self {
+        Self {
+            command_timeout: Some(command_timeout),
+            ..self
+        }
+    }
+
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    /// Whether the pool should run `is_valid` on every checkout. Only Sentinel needs it,
+    /// since a failover leaves connections open to the demoted master. Connections that
+    /// hit an IO error or timeout are poisoned and dropped by `has_broken` instead.
+    pub fn test_on_check_out(&self) -> bool {
+        match self.endpoint {
+            Endpoint::Client(_) => false,
+            #[cfg(feature = "sentinel")]
+            Endpoint::Sentinel { .. } => true,
+        }
+    }
+
+    fn client(&self) -> Result<Client, RedisError> {
+        match &self.endpoint {
+            Endpoint::Client(client) => Ok(client.clone()),
+            #[cfg(feature = "sentinel")]
+            Endpoint::Sentinel {
+                sentinel,
+                service_name,
+            } => {
+                let mut sentinel = sentinel.lock().map_err(|e| {
+                    RedisError::ConnectionError(redis::RedisError::from((
+                        ErrorKind::ClientError,
+                        "Sentinel is unavailable",
+                        e.to_string(),
+                    )))
+                })?;
+                sentinel
+                    .master_for(service_name, None)
+                    .map_err(connection_error)
+            }
+        }
+    }
+}
+
+/// A pooled connection. A command that fails with an IO error, including a timeout, may
+/// leave its reply unread on the socket, where the next command would read it as its
+/// own, so the connection is poisoned and the pool drops it instead of reusing it.
+pub struct RedisConnection {
+    connection: Connection,
+    poisoned: bool,
+}
+
+impl RedisConnection {
+    fn new(connection: Connection) -> Self {
+        Self {
+            connection,
+            poisoned: false,
+        }
+    }
+
+    /// Drops the connection when it is returned to the pool.
+    pub fn poison(&mut self) {
+        self.poisoned = true;
+    }
+
+    /// Commands sent on the returned connection do not poison it.
+    pub fn get_mut(&mut self) -> &mut Connection {
+        &mut self.connection
+    }
+
+    pub fn into_inner(self) -> Connection {
+        self.connection
+    }
+
+    fn check<T>(&mut self, result: RedisResult<T>) -> RedisResult<T> {
+        if result.as_ref().is_err_and(|e| e.is_io_error()) {
+            self.poison();
+        }
+        result
+    }
+}
+
+impl ConnectionLike for RedisConnection {
+    fn req_packed_command(&mut self, cmd: &[u8]) -> RedisResult<Value> {
+        let result = self.connection.req_packed_command(cmd);
+        self.check(result)
+    }
+
+    fn req_packed_commands(
+        &mut self,
+        cmd: &[u8],
+        offset: usize,
+        count: usize,
+    ) -> RedisResult<Vec<Value>> {
+        let result = self.connection.req_packed_commands(cmd, offset, count);
+        self.check(result)
+    }
+
+    fn get_db(&self) -> i64 {
+        self.connection.get_db()
+    }
+
+    fn check_connection(&mut self) -> bool {
+        self.connection.check_connection()
+    }
+
+    fn is_open(&self) -> bool {
+        self.connection.is_open()
+    }
+}
+
+impl r2d2::ManageConnection for RedisConnectionManager {
+    type Connection = RedisConnection;
+    type Error = RedisError;
+
+    fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        let client = self.client()?;
+        let mut connection = match self.connect_timeout {
+            Some(timeout) => client.get_connection_with_timeout(timeout),
+            None => client.get_connection(),
+        }
+        .map_err(connection_error)?;
+        connection
+            .set_read_timeout(self.command_timeout)
+            .and_then(|_| connection.set_write_timeout(self.command_timeout))
+            .map_err(connection_error)?;
+        // The TLS handshake only happens on first use, so force it here to surface
+        // certificate problems as connection failures rather than query failures.
+        if is_tls(&client) {
+            redis::cmd("PING")
+                .query::<()>(&mut connection)
+                .map_err(handshake_error)?;
+        }
+        if let Some(db) = self.db {
+            redis::cmd("SELECT")
+                .arg(db)
+                .query::<()>(&mut connection)
+                .map_err(query_error)?;
+        }
+        Ok(RedisConnection::new(connection))
+    }
+
+    fn is_valid(&self, connection: &mut Self::Connection) -> Result<(), Self::Error> {
+        ping(connection)?;
+        match self.endpoint {
+            Endpoint::Client(_) => Ok(()),
+            // After a failover the old master is demoted to a replica, so its
+            // connections are dropped and replaced by ones to the new master.
+            #[cfg(feature = "sentinel")]
+            Endpoint::Sentinel { .. } => {
+                let role: Vec<Value> = redis::cmd("ROLE")
+                    .query(connection)
+                    .map_err(connection_error)?;
+                let role = role
+                    .first()
+                    .map(String::from_redis_value)
+                    .transpose()
+                    .map_err(query_error)?;
+                match role.as_deref() {
+                    Some("master") => Ok(()),
+                    _ => Err(RedisError::ConnectionError(redis::RedisError::from((
+                        ErrorKind::ReadOnly,
+                        "Connection is no longer to the master",
+                    )))),
+                }
+            }
+        }
+    }
+
+    fn has_broken(&self, connection: &mut Self::Connection) -> bool {
+        connection.poisoned || !connection.is_open()
+    }
+}
+
+/// How long `drain` waits for a connection that is still in use.
+pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Checks out every connection in `pool` and sends each a `QUIT`, so the server closes
+/// them rather than seeing a reset. Connections that are not returned within
+/// `DRAIN_TIMEOUT` are left alone, and failures are ignored since the server may
+/// already be gone.
+pub fn drain(pool: &Pool<RedisConnectionManager>) {
+    let open = pool.state().connections;
+    let mut connections = Vec::new();
+    for _ in 0..open {
+        match pool.get_timeout(DRAIN_TIMEOUT) {
+            Ok(connection) => connections.push(connection),
+            Err(_) => break,
+        }
+    }
+    for connection in &mut connections {
+        let _ = redis::cmd("QUIT").query::<()>(&mut **connection);
+    }
+}
+
+/// Sends a `PING` with a fresh message and checks it is echoed back, so a connection that
+/// is out of step with its replies is replaced.
+fn ping(connection: &mut RedisConnection) -> Result<(), RedisError> {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let message = NEXT.fetch_add(1, Ordering::Relaxed).to_string();
+    let reply: String = redis::cmd("PING")
+        .arg(&message)
+        .query(connection)
+        .map_err(connection_error)?;
+    if reply != message {
+        return Err(RedisError::ConnectionError(redis::RedisError::from((
+            ErrorKind::ResponseError,
+            "Connection is out of step with its replies",
+        ))));
+    }
+    Ok(())
+}
+
+fn is_tls(client: &Client) -> bool {
+    matches!(
+        client.get_connection_info().addr,
+        ConnectionAddr::TcpTls { .. }
+    )
+}
+
+/// Keeps the most recent connection error, which r2d2 otherwise only reports as a string.
+#[derive(Clone, Debug, Default)]
+pub struct LastError(Arc<Mutex<Option<RedisError>>>);
+
+impl LastError {
+    pub fn take(&self) -> Option<RedisError> {
+        self.0.lock().ok()?.take()
+    }
+
+    fn set(&self, error: RedisError) {
+        if let Ok(mut last_error) = self.0.lock() {
+            *last_error = Some(error);
+        }
+    }
+}
+
+impl r2d2::HandleError<RedisError> for LastError {
+    fn handle_error(&self, error: RedisError) {
+        self.set(error);
+    }
+}
+
+impl r2d2::HandleError<redis::RedisError> for LastError {
+    fn handle_error(&self, error: redis::RedisError) {
+        self.set(connection_error(error));
+    }
+}
+
+pub fn connection_error(error: redis::RedisError) -> RedisError {
+    if is_tls_error(&error) {
+        RedisError::TlsError(error.to_string())
+    } else if error.is_timeout() {
+        RedisError::TimeoutError(error.to_string())
+    } else {
+        RedisError::ConnectionError(error)
+    }
+}
+
+pub fn query_error(error: redis::RedisError) -> RedisError {
+    if error.is_io_error() {
+        connection_error(error)
+    } else {
+        RedisError::QueryError(error)
+    }
+}
+
+fn handshake_error(error: redis::RedisError) -> RedisError {
+    if error.is_io_error() && !error.is_timeout() && !error.is_connection_dropped() {
+        RedisError::TlsError(error.to_string())
+    } else {
+        connection_error(error)
+    }
+}
+
+#[allow(deprecated)]
+fn is_tls_error(error: &redis::RedisError) -> bool {
+    // `redis` reports TLS configuration failures with this fixed description
+    std::error::Error::description(error) == "TLS error"
+}
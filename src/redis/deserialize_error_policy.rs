@@ -0,0 +1,74 @@
+use std::{fmt, sync::Arc};
+
+use crate::redis::error::RedisError;
+
+/// How batch reads such as `get_many` and `keys` treat a value or key that cannot be
+/// deserialized. Single-key reads always fail.
+#[derive(Clone, Default)]
+pub enum DeserializeErrorPolicy {
+    #[default]
+    Fail,
+    /// Drops the entry and passes the error to the callback, for example to log it or
+    /// queue the key for repair.
+    SkipAndLog(Arc<dyn Fn(&RedisError) + Send + Sync>),
+}
+
+impl DeserializeErrorPolicy {
+    pub fn skip_and_log(f: impl Fn(&RedisError) + Send + Sync + 'static) -> Self {
+        Self::SkipAndLog(Arc::new(f))
+    }
+
+    /// `Ok(None)` when a deserialization error is skipped. Other errors are returned
+    /// under either policy.
+    pub fn apply<T>(&self, result: Result<T, RedisError>) -> Result<Option<T>, RedisError> {
+        match (self, result) {
+            (_, Ok(value)) => Ok(Some(value)),
+            (Self::SkipAndLog(f), Err(e @ RedisError::DeserializeError(_, _))) => {
+                f(&e);
+                Ok(None)
+            }
+            (_, Err(e)) => Err(e),
+        }
+    }
+}
+
+impl fmt::Debug for DeserializeErrorPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fail => f.write_str("Fail"),
+            Self::SkipAndLog(_) => f.write_str("SkipAndLog"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::DeserializeErrorPolicy;
+    use crate::redis::error::RedisError;
+
+    fn deserialize_error() -> RedisError {
+        RedisError::DeserializeError("key".to_string(), "bad".to_string())
+    }
+
+    #[test]
+    fn test_fail_returns_the_error() {
+        let ret = DeserializeErrorPolicy::Fail.apply::<u64>(Err(deserialize_error()));
+        assert!(matches!(ret, Err(RedisError::DeserializeError(_, _))));
+    }
+
+    #[test]
+    fn test_skip_and_log_reports_only_deserialization_errors() {
+        let skipped = Arc::new(Mutex::new(Vec::new()));
+        let policy = DeserializeErrorPolicy::skip_and_log({
+            let skipped = skipped.clone();
+            move |e| skipped.lock().unwrap().push(e.to_string())
+        });
+        let ret = policy.apply::<u64>(Err(deserialize_error()));
+        assert!(matches!(ret, Ok(None)));
+        let ret = policy.apply::<u64>(Err(RedisError::Conflict("key".to_string())));
+        assert!(matches!(ret, Err(RedisError::Conflict(_))));
+        assert_eq!(skipped.lock().unwrap().len(), 1);
+    }
+}
@@ -1,11 +1,126 @@
+use redis::ErrorKind;
+
 #[derive(Debug, thiserror::Error)]
 pub enum RedisError {
     #[error("Redis connection error: {0}")]
-    ConnectionError(String),
+    ConnectionError(#[source] redis::RedisError),
+    #[error("Invalid Redis URL: {0}")]
+    InvalidUrl(String),
+    #[error("Redis connection timed out: {0}")]
+    TimeoutError(String),
+    #[error("Redis TLS error: {0}")]
+    TlsError(String),
     #[error("Redis query error: {0}")]
-    QueryError(String),
+    QueryError(#[source] redis::RedisError),
     #[error("Unable to serialize value for key \"{0}\": {1}")]
     SerializeError(String, String),
     #[error("Unable to deserialize value for key \"{0}\": {1}")]
     DeserializeError(String, String),
+    #[error("Value for Redis key \"{key}\" is {size} bytes, over the limit of {limit}")]
+    ValueTooLarge {
+        key: String,
+        size: usize,
+        limit: usize,
+    },
+    #[error("Redis key \"{0}\" does not exist")]
+    KeyNotFound(String),
+    #[error("Redis key \"{0}\" already exists")]
+    KeyExists(String),
+    #[error("Redis key \"{0}\" was modified concurrently")]
+    Conflict(String),
+    #[error("Only {acknowledged} of {requested} replicas acknowledged the write to \"{key}\"")]
+    ReplicationTimeout {
+        key: String,
+        acknowledged: usize,
+        requested: usize,
+    },
+    #[error("Redis command {0} is not supported by the server")]
+    UnsupportedCommand(String),
+    #[error("Redis command failed after {0} attempts: {1}")]
+    RetryError(u32, Box<RedisError>),
+}
+
+impl RedisError {
+    /// Whether the same command may succeed if sent again: dropped connections, timeouts
+    /// and cluster conditions such as `TRYAGAIN` and `CLUSTERDOWN`. An exhausted
+    /// `RetryError` is not retryable.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            RedisError::ConnectionError(e) => !matches!(
+                e.kind(),
+                ErrorKind::InvalidClientConfig | ErrorKind::AuthenticationFailed
+            ),
+            RedisError::TimeoutError(_) => true,
+            RedisError::QueryError(e) => {
+                e.is_io_error()
+                    || matches!(
+                        e.kind(),
+                        ErrorKind::TryAgain
+                            | ErrorKind::ClusterDown
+                            | ErrorKind::MasterDown
+                            | ErrorKind::BusyLoadingError
+                    )
+            }
+            RedisError::TlsError(_)
+            | RedisError::InvalidUrl(_)
+            | RedisError::SerializeError(_, _)
+            | RedisError::DeserializeError(_, _)
+            | RedisError::ValueTooLarge { .. }
+            | RedisError::KeyNotFound(_)
+            | RedisError::KeyExists(_)
+            | RedisError::Conflict(_)
+            | RedisError::ReplicationTimeout { .. }
+            | RedisError::UnsupportedCommand(_)
+            | RedisError::RetryError(_, _) => false,
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    pub(crate) fn variant(&self) -> &'static str {
+        match self {
+            RedisError::ConnectionError(_) => "ConnectionError",
+            RedisError::InvalidUrl(_) => "InvalidUrl",
+            RedisError::TimeoutError(_) => "TimeoutError",
+            RedisError::TlsError(_) => "TlsError",
+            RedisError::QueryError(_) => "QueryError",
+            RedisError::SerializeError(_, _) => "SerializeError",
+            RedisError::DeserializeError(_, _) => "DeserializeError",
+            RedisError::ValueTooLarge { .. } => "ValueTooLarge",
+            RedisError::KeyNotFound(_) => "KeyNotFound",
+            RedisError::KeyExists(_) => "KeyExists",
+            RedisError::Conflict(_) => "Conflict",
+            RedisError::ReplicationTimeout { .. } => "ReplicationTimeout",
+            RedisError::UnsupportedCommand(_) => "UnsupportedCommand",
+            RedisError::RetryError(_, _) => "RetryError",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use redis::ErrorKind;
+
+    use super::RedisError;
+
+    fn redis_error(kind: ErrorKind) -> redis::RedisError {
+        redis::RedisError::from((kind, "error"))
+    }
+
+    #[test]
+    fn test_is_retryable_for_transient_errors() {
+        assert!(RedisError::ConnectionError(redis_error(ErrorKind::IoError)).is_retryable());
+        assert!(RedisError::TimeoutError("timed out".to_string()).is_retryable());
+        assert!(RedisError::QueryError(redis_error(ErrorKind::TryAgain)).is_retryable());
+        assert!(RedisError::QueryError(redis_error(ErrorKind::ClusterDown)).is_retryable());
+    }
+
+    #[test]
+    fn test_is_not_retryable_for_logic_errors() {
+        let invalid_url = redis_error(ErrorKind::InvalidClientConfig);
+        assert!(!RedisError::ConnectionError(invalid_url).is_retryable());
+        assert!(!RedisError::QueryError(redis_error(ErrorKind::TypeError)).is_retryable());
+        let error = RedisError::SerializeError("key".to_string(), "bad".to_string());
+        assert!(!error.is_retryable());
+        assert!(!RedisError::Conflict("key".to_string()).is_retryable());
+    }
 }
@@ -1,8 +1,50 @@
-use crate::redis::{commands::Command, error::RedisError};
+use std::time::Duration;
 
+use crate::redis::{commands::Command, error::RedisError, ttl_status::TtlStatus};
+
+/// `execute_command` accepts any reply type, so the commands behind the storage traits
+/// also have wrappers that fix the reply type.
 pub trait ExecuteCommand {
     fn execute_command<T: redis::FromRedisValue>(
         &self,
         command: Command,
+    ) -> Result<T, RedisError> {
+        self.execute_command_ref(&command)
+    }
+
+    /// Builds the request from a borrowed command, so a command can be sent again, or
+    /// sent from borrowed data, without cloning it.
+    fn execute_command_ref<T: redis::FromRedisValue>(
+        &self,
+        command: &Command,
     ) -> Result<T, RedisError>;
+
+    /// The stored bytes, or `None` if the key is missing.
+    fn execute_get(&self, key: String) -> Result<Option<Vec<u8>>, RedisError> {
+        self.execute_command(Command::get(key))
+    }
+
+    fn execute_set(
+        &self,
+        key: String,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+    ) -> Result<(), RedisError> {
+        self.execute_command(Command::set(key, value, ttl))
+    }
+
+    /// Returns `true` if the key existed.
+    fn execute_delete(&self, key: String) -> Result<bool, RedisError> {
+        self.execute_command(Command::delete(key))
+    }
+
+    fn execute_ttl(&self, key: String) -> Result<TtlStatus, RedisError> {
+        let seconds: i64 = self.execute_command(Command::ttl(key))?;
+        Ok(TtlStatus::from_secs(seconds))
+    }
+
+    fn execute_pttl(&self, key: String) -> Result<TtlStatus, RedisError> {
+        let millis: i64 = self.execute_command(Command::pttl(key))?;
+        Ok(TtlStatus::from_millis(millis))
+    }
 }
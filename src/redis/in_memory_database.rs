@@ -0,0 +1,317 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use lushus_storage::{Storage, StorageRead, StorageTemp, StorageWrite, Table};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::redis::{
+    error::RedisError,
+    redis_key::RedisKey,
+    serialization::{deserialize_value, serialize_value, JsonSerializer, Serializer},
+    ttl_status::TtlStatus,
+};
+
+/// A stand-in for `RedisDatabase` in tests that keeps values in a `HashMap`. Values go
+/// through the same serializer, and expired keys disappear the next time they are
+/// accessed. Clones share the same entries, like clones sharing a Redis pool.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryDatabase<S = JsonSerializer> {
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+    ttl: Option<Duration>,
+    serializer: S,
+}
+
+#[derive(Debug)]
+struct Entry {
+    value: Vec<u8>,
+    expires_at: Option<Instant>,
+}
+
+impl Entry {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+impl InMemoryDatabase {
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_serializer(ttl, JsonSerializer)
+    }
+
+    /// Keys are stored without expiry, as with `RedisDatabase::new_persistent`.
+    pub fn new_persistent() -> Self {
+        Self::default()
+    }
+}
+
+impl<S: Serializer> InMemoryDatabase<S> {
+    pub fn with_serializer(ttl: Duration, serializer: S) -> Self {
+        Self {
+            entries: Arc::default(),
+            ttl: Some(ttl),
+            serializer,
+        }
+    }
+
+    pub fn insert_with_ttl<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+        ttl: Duration,
+    ) -> Result<Option<TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        self._insert::<TableType>(key, value, Some(ttl))
+    }
+
+    pub fn ttl_status<TableType>(&self, key: &TableType::Key) -> Result<TtlStatus, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = key.to_redis_key();
+        let now = Instant::now();
+        let status = match self.live_entries(&key, now).get(&key) {
+            None => TtlStatus::Missing,
+            Some(Entry {
+                expires_at: None, ..
+            }) => TtlStatus::NoExpiry,
+            Some(Entry {
+                expires_at: Some(expires_at),
+                ..
+            }) => TtlStatus::Expires(expires_at.duration_since(now)),
+        };
+        Ok(status)
+    }
+
+    fn entries(&self) -> MutexGuard<'_, HashMap<String, Entry>> {
+        // A panic while holding the lock cannot leave an entry half written
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the entries after removing `key` if it has expired.
+    fn live_entries(&self, key: &str, now: Instant) -> MutexGuard<'_, HashMap<String, Entry>> {
+        let mut entries = self.entries();
+        if entries.get(key).is_some_and(|entry| entry.is_expired(now)) {
+            entries.remove(key);
+        }
+        entries
+    }
+
+    fn _get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, RedisError> {
+        let data = self
+            .live_entries(key, Instant::now())
+            .get(key)
+            .map(|entry| entry.value.clone());
+        deserialize_value(&self.serializer, key, data)
+    }
+
+    fn _insert<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+        ttl: Option<Duration>,
+    ) -> Result<Option<TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = key.to_redis_key();
+        let previous = self._get(&key)?;
+        let value = serialize_value(&self.serializer, &key, value)?;
+        let expires_at = ttl.map(|ttl| Instant::now() + ttl);
+        self.entries().insert(key, Entry { value, expires_at });
+        Ok(previous)
+    }
+}
+
+impl<S: Serializer> Storage for InMemoryDatabase<S> {
+    type Error = RedisError;
+}
+
+impl<S, TableType> StorageRead<TableType> for InMemoryDatabase<S>
+where
+    S: Serializer,
+    TableType: Table,
+    TableType::Key: RedisKey,
+    TableType::OwnedValue: DeserializeOwned,
+{
+    fn get(
+        &self,
+        key: &TableType::Key,
+    ) -> Result<Option<Cow<'_, TableType::OwnedValue>>, Self::Error> {
+        let key = key.to_redis_key();
+        let value = self._get(&key)?;
+        Ok(value.map(Cow::Owned))
+    }
+
+    fn exists(&self, key: &TableType::Key) -> Result<bool, Self::Error> {
+        let key = key.to_redis_key();
+        Ok(self.live_entries(&key, Instant::now()).contains_key(&key))
+    }
+}
+
+impl<S, TableType> StorageWrite<TableType> for InMemoryDatabase<S>
+where
+    S: Serializer,
+    TableType: Table,
+    TableType::Key: RedisKey,
+    TableType::Value: Serialize,
+    TableType::OwnedValue: DeserializeOwned,
+{
+    fn insert(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+    ) -> Result<Option<TableType::OwnedValue>, Self::Error> {
+        let ttl = self.ttl;
+        self._insert::<TableType>(key, value, ttl)
+    }
+
+    fn remove(
+        &mut self,
+        key: &TableType::Key,
+    ) -> Result<Option<TableType::OwnedValue>, Self::Error> {
+        let key = key.to_redis_key();
+        let previous = self._get(&key)?;
+        self.entries().remove(&key);
+        Ok(previous)
+    }
+}
+
+impl<S, TableType> StorageTemp<TableType> for InMemoryDatabase<S>
+where
+    S: Serializer,
+    TableType: Table,
+    TableType::Key: RedisKey,
+{
+    fn ttl(&self, key: &TableType::Key) -> Result<Duration, Self::Error> {
+        let status = self.ttl_status::<TableType>(key)?;
+        Ok(status.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use lushus_storage::{StorageAsMut, StorageAsRef, Table};
+
+    use super::InMemoryDatabase;
+    use crate::redis::ttl_status::TtlStatus;
+
+    #[derive(Copy, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Foo {
+        bar: u64,
+    }
+
+    struct FooTable {}
+
+    impl Table for FooTable {
+        type Key = String;
+        type OwnedKey = Self::Key;
+        type Value = Foo;
+        type OwnedValue = Self::Value;
+    }
+
+    #[test]
+    fn test_insert_returns_the_previous_value() {
+        let mut database = InMemoryDatabase::new(Duration::from_secs(1));
+        let key = "key".to_string();
+        let first = Foo { bar: 1 };
+        let second = Foo { bar: 2 };
+        let mut storage = database.storage_as_mut::<FooTable>();
+        let previous = storage.insert(&key, &first).expect("Failed to insert");
+        assert_eq!(previous, None);
+        let previous = storage.insert(&key, &second).expect("Failed to insert");
+        assert_eq!(previous, Some(first));
+        let value = database
+            .storage_as_ref::<FooTable>()
+            .get(&key)
+            .expect("Failed to get key");
+        assert_eq!(value.map(|v| v.into_owned()), Some(second));
+    }
+
+    #[test]
+    fn test_remove_returns_the_previous_value() {
+        let mut database = InMemoryDatabase::new_persistent();
+        let key = "key".to_string();
+        let foo = Foo { bar: 42 };
+        let mut storage = database.storage_as_mut::<FooTable>();
+        storage.insert(&key, &foo).expect("Failed to insert");
+        let previous = storage.remove(&key).expect("Failed to remove");
+        assert_eq!(previous, Some(foo));
+        let exists = database
+            .storage_as_ref::<FooTable>()
+            .exists(&key)
+            .expect("Failed to check key");
+        assert!(!exists);
+    }
+
+    #[test]
+    fn test_keys_expire_after_the_ttl() {
+        let mut database = InMemoryDatabase::new(Duration::from_millis(10));
+        let key = "key".to_string();
+        database
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &Foo { bar: 42 })
+            .expect("Failed to insert");
+        thread::sleep(Duration::from_millis(20));
+        let exists = database
+            .storage_as_ref::<FooTable>()
+            .exists(&key)
+            .expect("Failed to check key");
+        assert!(!exists);
+        let status = database
+            .ttl_status::<FooTable>(&key)
+            .expect("Failed to get TTL");
+        assert_eq!(status, TtlStatus::Missing);
+    }
+
+    #[test]
+    fn test_ttl_status_reports_persistent_keys() {
+        let mut database = InMemoryDatabase::new_persistent();
+        let key = "key".to_string();
+        database
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &Foo { bar: 42 })
+            .expect("Failed to insert");
+        let status = database
+            .ttl_status::<FooTable>(&key)
+            .expect("Failed to get TTL");
+        assert_eq!(status, TtlStatus::NoExpiry);
+        database
+            .insert_with_ttl::<FooTable>(&key, &Foo { bar: 43 }, Duration::from_secs(30))
+            .expect("Failed to insert");
+        let status = database
+            .ttl_status::<FooTable>(&key)
+            .expect("Failed to get TTL");
+        assert!(matches!(status, TtlStatus::Expires(d) if d > Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn test_clones_share_entries() {
+        let mut database = InMemoryDatabase::new_persistent();
+        let clone = database.clone();
+        let key = "key".to_string();
+        database
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &Foo { bar: 42 })
+            .expect("Failed to insert");
+        let exists = clone
+            .storage_as_ref::<FooTable>()
+            .exists(&key)
+            .expect("Failed to check key");
+        assert!(exists);
+    }
+}
@@ -0,0 +1,88 @@
+use std::any;
+
+use crate::redis::{error::RedisError, subscription::Subscription};
+
+/// Yields a `(table, key)` pair for each key written or removed through a `RedisDatabase`
+/// that publishes to the channel, for evicting the key from a local cache. `table` is the
+/// type name of the table and `key` is the table key as `RedisKey` formats it, without the
+/// database prefix.
+pub struct InvalidationListener {
+    subscription: Subscription,
+}
+
+impl InvalidationListener {
+    pub(crate) fn new(subscription: Subscription) -> Self {
+        Self { subscription }
+    }
+}
+
+impl Iterator for InvalidationListener {
+    type Item = Result<(String, String), RedisError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let message = self.subscription.next()?;
+        Some(message.and_then(|message| {
+            serde_json::from_str(&message)
+                .map_err(|e| RedisError::DeserializeError(message, e.to_string()))
+        }))
+    }
+}
+
+/// Encoded as a JSON array, so neither the table nor the key needs escaping.
+pub fn event<TableType>(key: &str) -> String {
+    serde_json::json!([any::type_name::<TableType>(), key]).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use lushus_storage::{StorageAsMut, Table};
+
+    use super::event;
+    use crate::redis::{
+        redis_database::RedisDatabase,
+        test_support::{unique_key, url},
+    };
+
+    struct FooTable {}
+
+    impl Table for FooTable {
+        type Key = String;
+        type OwnedKey = Self::Key;
+        type Value = u64;
+        type OwnedValue = Self::Value;
+    }
+
+    #[test]
+    fn test_event_lists_the_table_and_key() {
+        let table = std::any::type_name::<FooTable>();
+        assert_eq!(
+            event::<FooTable>("a\"b"),
+            format!("[\"{table}\",\"a\\\"b\"]")
+        );
+    }
+
+    #[test]
+    fn test_writes_publish_invalidations() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let channel = unique_key("invalidations");
+        redis.set_invalidation_channel(Some(channel.clone()));
+        let mut listener = redis
+            .invalidation_listener(&channel)
+            .expect("Failed to subscribe");
+        let key = unique_key("invalidated");
+        let mut storage = redis.storage_as_mut::<FooTable>();
+        storage.insert(&key, &42).expect("Failed to insert");
+        storage.remove(&key).expect("Failed to remove");
+        let table = std::any::type_name::<FooTable>().to_string();
+        for _ in 0..2 {
+            let event = listener
+                .next()
+                .expect("Listener ended")
+                .expect("Failed to receive event");
+            assert_eq!(event, (table.clone(), key.clone()));
+        }
+    }
+}
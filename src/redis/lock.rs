@@ -0,0 +1,90 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::redis::{
+    commands::Command, error::RedisError, execute_command::ExecuteCommand,
+    redis_database::RedisDatabase, serialization::Serializer,
+};
+
+// Only delete the lock if it still holds our token, so a holder whose lock already
+// expired cannot release a lock that someone else has since acquired.
+const RELEASE: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+else
+    return 0
+end
+"#;
+
+pub struct LockGuard<'a, S: Serializer> {
+    database: &'a RedisDatabase<S>,
+    key: String,
+    token: String,
+    released: bool,
+}
+
+impl<'a, S: Serializer> LockGuard<'a, S> {
+    pub(crate) fn new(database: &'a RedisDatabase<S>, key: String, token: String) -> Self {
+        Self {
+            database,
+            key,
+            token,
+            released: false,
+        }
+    }
+
+    /// Returns `false` if the lock had already expired.
+    pub fn release(mut self) -> Result<bool, RedisError> {
+        self.released = true;
+        self.unlock()
+    }
+
+    fn unlock(&self) -> Result<bool, RedisError> {
+        let command = Command::eval(
+            RELEASE.to_string(),
+            vec![self.key.clone()],
+            vec![self.token.clone()],
+        );
+        self.database.execute_command(command)
+    }
+}
+
+impl<S: Serializer> Drop for LockGuard<'_, S> {
+    fn drop(&mut self) {
+        if !self.released {
+            let _ = self.unlock();
+        }
+    }
+}
+
+/// Unique per lock acquisition, though not cryptographically random.
+pub fn token() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let [high, low] = [RandomState::new(), RandomState::new()].map(|state| {
+        let mut hasher = state.build_hasher();
+        hasher.write_u128(nanos);
+        hasher.write_u64(counter);
+        hasher.write_u32(std::process::id());
+        hasher.finish()
+    });
+    format!("{high:016x}{low:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::token;
+
+    #[test]
+    fn test_token_is_unique() {
+        assert_ne!(token(), token());
+    }
+}
@@ -0,0 +1,32 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use crate::redis::error::RedisError;
+
+pub trait Metrics: Send + Sync {
+    /// Called once per attempt, so a retried command is reported every time it runs.
+    /// `name` is the Redis command, or `PIPELINE` and `MULTI` for batches.
+    fn on_command(&self, name: &str, duration: Duration, result: Result<(), &RedisError>);
+}
+
+#[derive(Clone, Default)]
+pub struct MetricsHook(Option<Arc<dyn Metrics>>);
+
+impl MetricsHook {
+    pub fn new(metrics: Arc<dyn Metrics>) -> Self {
+        Self(Some(metrics))
+    }
+
+    pub fn on_command<T>(&self, name: &str, duration: Duration, result: &Result<T, RedisError>) {
+        if let Some(metrics) = &self.0 {
+            metrics.on_command(name, duration, result.as_ref().map(|_| ()));
+        }
+    }
+}
+
+impl fmt::Debug for MetricsHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MetricsHook")
+            .field(&self.0.is_some())
+            .finish()
+    }
+}
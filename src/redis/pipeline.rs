@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use lushus_storage::Table;
+use serde::Serialize;
+
+use crate::redis::{
+    commands::Command, error::RedisError, redis_database::RedisDatabase, redis_key::RedisKey,
+    serialization::Serializer,
+};
+
+/// Batches commands into a single round trip without the atomicity of a transaction, so
+/// other clients may observe some of them applied before the rest.
+pub struct Pipeline<'a, S> {
+    database: &'a RedisDatabase<S>,
+    commands: Vec<Command>,
+}
+
+impl<'a, S: Serializer> Pipeline<'a, S> {
+    pub(crate) fn new(database: &'a RedisDatabase<S>) -> Self {
+        Self {
+            database,
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn set<TableType>(
+        self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+    ) -> Result<Self, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let ttl = self.database.default_ttl();
+        self.push_set::<TableType>(key, value, ttl)
+    }
+
+    pub fn set_with_ttl<TableType>(
+        self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+        ttl: Duration,
+    ) -> Result<Self, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        self.push_set::<TableType>(key, value, Some(ttl))
+    }
+
+    fn push_set<TableType>(
+        mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+        ttl: Option<Duration>,
+    ) -> Result<Self, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let key = self.database.table_key::<TableType>(key);
+        let value = self.database.encode(&key, value)?;
+        self.commands.push(Command::set(key, value, ttl));
+        Ok(self)
+    }
+
+    pub fn delete<TableType>(mut self, key: &TableType::Key) -> Self
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.database.table_key::<TableType>(key);
+        self.commands.push(Command::delete(key));
+        self
+    }
+
+    /// Sends every queued command and discards their replies.
+    pub fn execute(self) -> Result<(), RedisError> {
+        if self.commands.is_empty() {
+            return Ok(());
+        }
+        self.database.execute_pipeline(self.commands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use lushus_storage::Table;
+
+    use super::Pipeline;
+    use crate::redis::redis_database::RedisDatabase;
+
+    struct CounterTable {}
+
+    impl Table for CounterTable {
+        type Key = String;
+        type OwnedKey = Self::Key;
+        type Value = u64;
+        type OwnedValue = Self::Value;
+    }
+
+    #[test]
+    fn test_pipeline_accumulates_every_command() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::new("redis://localhost:6379", ttl).expect("Invalid Redis URL");
+        let key = "key".to_string();
+        let pipeline = Pipeline::new(&redis)
+            .set::<CounterTable>(&key, &42)
+            .expect("Failed to serialize")
+            .set_with_ttl::<CounterTable>(&key, &69, ttl)
+            .expect("Failed to serialize")
+            .delete::<CounterTable>(&key);
+        assert_eq!(pipeline.commands.len(), 3);
+    }
+}
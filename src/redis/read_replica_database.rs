@@ -0,0 +1,132 @@
+use std::{borrow::Cow, time::Duration};
+
+use lushus_storage::{Storage, StorageRead, StorageTemp, Table};
+use serde::de::DeserializeOwned;
+
+use crate::redis::{
+    error::RedisError,
+    redis_database::RedisDatabase,
+    redis_key::RedisKey,
+    serialization::{JsonSerializer, Serializer},
+    ttl_status::TtlStatus,
+};
+
+/// Serves reads from a replica. It only implements `StorageRead` and `StorageTemp`, so
+/// writes are rejected at compile time rather than by the replica.
+///
+/// `READONLY` is not sent, since standalone replicas serve reads without it and reject
+/// it when cluster support is disabled.
+#[derive(Clone, Debug)]
+pub struct ReadReplicaDatabase<S = JsonSerializer> {
+    database: RedisDatabase<S>,
+}
+
+impl ReadReplicaDatabase {
+    pub fn new(url: &str) -> Result<Self, RedisError> {
+        Ok(Self {
+            database: RedisDatabase::new_persistent(url)?,
+        })
+    }
+}
+
+impl<S: Serializer> ReadReplicaDatabase<S> {
+    pub fn ttl_status<TableType>(&self, key: &TableType::Key) -> Result<TtlStatus, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        self.database.ttl_status::<TableType>(key)
+    }
+
+    pub fn get_many<TableType>(
+        &self,
+        keys: &[TableType::Key],
+    ) -> Result<Vec<Option<TableType::OwnedValue>>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey + Sized,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        self.database.get_many::<TableType>(keys)
+    }
+}
+
+/// Keeps the prefix, serializer and other options of `database`.
+impl<S> From<RedisDatabase<S>> for ReadReplicaDatabase<S> {
+    fn from(database: RedisDatabase<S>) -> Self {
+        Self { database }
+    }
+}
+
+impl<S: Serializer> Storage for ReadReplicaDatabase<S> {
+    type Error = RedisError;
+}
+
+impl<S, TableType> StorageRead<TableType> for ReadReplicaDatabase<S>
+where
+    S: Serializer,
+    TableType: Table,
+    TableType::Key: RedisKey,
+    TableType::OwnedValue: DeserializeOwned,
+{
+    fn get(
+        &self,
+        key: &TableType::Key,
+    ) -> Result<Option<Cow<'_, TableType::OwnedValue>>, Self::Error> {
+        StorageRead::<TableType>::get(&self.database, key)
+    }
+
+    fn exists(&self, key: &TableType::Key) -> Result<bool, Self::Error> {
+        StorageRead::<TableType>::exists(&self.database, key)
+    }
+}
+
+impl<S, TableType> StorageTemp<TableType> for ReadReplicaDatabase<S>
+where
+    S: Serializer,
+    TableType: Table,
+    TableType::Key: RedisKey,
+{
+    fn ttl(&self, key: &TableType::Key) -> Result<Duration, Self::Error> {
+        StorageTemp::<TableType>::ttl(&self.database, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, time::Duration};
+
+    use lushus_storage::{StorageAsMut, StorageAsRef, Table};
+
+    use super::ReadReplicaDatabase;
+    use crate::redis::{
+        redis_database::RedisDatabase,
+        test_support::{unique_key, url},
+    };
+
+    struct CounterTable {}
+
+    impl Table for CounterTable {
+        type Key = String;
+        type OwnedKey = Self::Key;
+        type Value = u64;
+        type OwnedValue = Self::Value;
+    }
+
+    #[test]
+    fn test_get_reads_values_written_through_the_master() {
+        let ttl = Duration::from_secs(1);
+        let mut master = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let replica = ReadReplicaDatabase::new(&url()).expect("Unable to connect to Redis");
+        let key = unique_key("read_replica_get");
+        master
+            .storage_as_mut::<CounterTable>()
+            .insert(&key, &42)
+            .expect("Failed to insert into Redis");
+        let ret = replica
+            .storage_as_ref::<CounterTable>()
+            .get(&key)
+            .expect("Failed to get from Redis");
+        assert_eq!(ret, Some(Cow::Borrowed(&42)));
+    }
+}
@@ -0,0 +1,150 @@
+use std::{borrow::Cow, time::Duration};
+
+use lushus_storage::{Storage, StorageRead, StorageTemp, StorageWrite, Table};
+use r2d2::{Pool, PooledConnection};
+use redis::cluster::ClusterClient;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::redis::{
+    commands::Command,
+    connection_manager::{query_error, LastError},
+    error::RedisError,
+    execute_command::ExecuteCommand,
+    redis_database::DEFAULT_POOL_SIZE,
+    redis_key::RedisKey,
+    serialization::{deserialize_value, serialize_value, JsonSerializer},
+};
+
+#[derive(Clone)]
+pub struct RedisClusterDatabase {
+    pool: Pool<ClusterClient>,
+    last_error: LastError,
+    ttl: Duration,
+}
+
+impl RedisClusterDatabase {
+    /// Only the seed nodes need to be listed; the remaining nodes are discovered from them.
+    pub fn new(urls: &[&str], ttl: Duration) -> Result<Self, RedisError> {
+        let client = ClusterClient::new(urls.to_vec()).map_err(RedisError::ConnectionError)?;
+        let last_error = LastError::default();
+        let pool = Pool::builder()
+            .max_size(DEFAULT_POOL_SIZE)
+            .error_handler(Box::new(last_error.clone()))
+            .build_unchecked(client);
+        Ok(Self {
+            pool,
+            last_error,
+            ttl,
+        })
+    }
+
+    fn connection(&self) -> Result<PooledConnection<ClusterClient>, RedisError> {
+        self.last_error.take();
+        let connection = self.pool.get().map_err(|e| {
+            self.last_error
+                .take()
+                .unwrap_or_else(|| RedisError::TimeoutError(e.to_string()))
+        })?;
+        Ok(connection)
+    }
+
+    fn _get<T: DeserializeOwned>(&self, key: String) -> Result<Option<T>, RedisError> {
+        let data = self.execute_get(key.clone())?;
+        deserialize_value(&JsonSerializer, &key, data)
+    }
+}
+
+impl ExecuteCommand for RedisClusterDatabase {
+    fn execute_command_ref<T: redis::FromRedisValue>(
+        &self,
+        command: &Command,
+    ) -> Result<T, RedisError> {
+        let mut connection = self.connection()?;
+        let redis_command: redis::Cmd = command.into();
+        let result = redis_command.query(&mut *connection).map_err(query_error)?;
+        Ok(result)
+    }
+}
+
+impl Storage for RedisClusterDatabase {
+    type Error = RedisError;
+}
+
+impl<TableType> StorageRead<TableType> for RedisClusterDatabase
+where
+    TableType: Table,
+    TableType::Key: RedisKey,
+    TableType::OwnedValue: DeserializeOwned,
+{
+    fn get(
+        &self,
+        key: &TableType::Key,
+    ) -> Result<Option<Cow<'_, TableType::OwnedValue>>, Self::Error> {
+        let key = key.to_redis_key();
+        self._get(key)
+    }
+
+    fn exists(&self, key: &TableType::Key) -> Result<bool, Self::Error> {
+        let key = key.to_redis_key();
+        let command = Command::exists(key);
+        let data = self.execute_command::<bool>(command)?;
+        Ok(data)
+    }
+}
+
+impl<TableType> StorageWrite<TableType> for RedisClusterDatabase
+where
+    TableType: Table,
+    TableType::Key: RedisKey,
+    TableType::Value: Serialize,
+    TableType::OwnedValue: DeserializeOwned,
+{
+    fn insert(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+    ) -> Result<Option<TableType::OwnedValue>, Self::Error> {
+        let key = key.to_redis_key();
+        let previous = self._get(key.clone())?;
+        let value = serialize_value(&JsonSerializer, &key, value)?;
+        let ttl = self.ttl;
+        self.execute_set(key, value, Some(ttl))?;
+        Ok(previous)
+    }
+
+    fn remove(
+        &mut self,
+        key: &TableType::Key,
+    ) -> Result<Option<TableType::OwnedValue>, Self::Error> {
+        let key = key.to_redis_key();
+        let previous = self._get(key.clone())?;
+        self.execute_delete(key)?;
+        Ok(previous)
+    }
+}
+
+impl<TableType> StorageTemp<TableType> for RedisClusterDatabase
+where
+    TableType: Table,
+    TableType::Key: RedisKey,
+{
+    fn ttl(&self, key: &TableType::Key) -> Result<Duration, Self::Error> {
+        let key = key.to_redis_key();
+        let status = self.execute_ttl(key)?;
+        Ok(status.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RedisClusterDatabase;
+
+    #[test]
+    fn test_constructor() {
+        let urls = ["redis://localhost:7000", "redis://localhost:7001"];
+        let ttl = Duration::from_secs(1);
+        RedisClusterDatabase::new(&urls, ttl).expect("Unable to create Redis Cluster client");
+    }
+}
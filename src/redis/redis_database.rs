@@ -1,249 +1,3680 @@
-use std::{borrow::Cow, time::Duration};
+#[cfg(unix)]
+use std::path::Path;
+use std::{
+    any,
+    borrow::Cow,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use lushus_storage::{Storage, StorageRead, StorageTemp, StorageWrite, Table};
-use redis::{Client, Connection};
+use r2d2::{ManageConnection, Pool, PooledConnection};
+#[cfg(feature = "sentinel")]
+use redis::sentinel::Sentinel;
+use redis::{Client, Connection, ErrorKind, IntoConnectionInfo, Script};
+#[cfg(unix)]
+use redis::{ConnectionAddr, ConnectionInfo, RedisConnectionInfo};
 use serde::{de::DeserializeOwned, Serialize};
+use sha1_smol::Sha1;
 
-use crate::redis::{commands::Command, error::RedisError, execute_command::ExecuteCommand};
+#[cfg(feature = "tls")]
+use crate::redis::tls_config::TlsConfig;
+use crate::redis::{
+    commands::Command,
+    compression::Compression,
+    connection_manager::{self, query_error, LastError, RedisConnection, RedisConnectionManager},
+    deserialize_error_policy::DeserializeErrorPolicy,
+    error::RedisError,
+    execute_command::ExecuteCommand,
+    invalidation::{self, InvalidationListener},
+    lock::{self, LockGuard},
+    metrics::{Metrics, MetricsHook},
+    pipeline::Pipeline,
+    redis_key::RedisKey,
+    redis_type::RedisType,
+    replicas::Replicas,
+    retry_policy::RetryPolicy,
+    scan::{escape_pattern, Scan, SCAN_COUNT},
+    serialization::{deserialize_bytes, serialize_value, JsonSerializer, Serializer},
+    subscription::Subscription,
+    transaction::Transaction,
+    ttl_status::TtlStatus,
+};
+
+mod bits;
+mod builder;
+mod hashes;
+mod hyperloglogs;
+mod lists;
+mod sets;
+mod sorted_sets;
+
+pub use builder::RedisDatabaseBuilder;
+
+pub const DEFAULT_POOL_SIZE: u32 = 10;
+
+const OPTIMISTIC_ATTEMPTS: u32 = 5;
+
+// Only the increment that creates the counter starts the window, so later increments
+// cannot extend it.
+const INCREMENT_WITH_TTL: &str = r#"
+local created = redis.call("EXISTS", KEYS[1]) == 0
+local count = redis.call("INCRBY", KEYS[1], ARGV[1])
+if created then
+    redis.call("PEXPIRE", KEYS[1], ARGV[2])
+end
+return count
+"#;
 
 #[derive(Clone, Debug)]
-pub struct RedisDatabase {
-    client: Client,
-    ttl: Duration,
+pub struct RedisDatabase<S = JsonSerializer> {
+    pool: Pool<RedisConnectionManager>,
+    manager: RedisConnectionManager,
+    last_error: LastError,
+    ttl: Option<Duration>,
+    retry: Option<RetryPolicy>,
+    prefix: Option<String>,
+    serializer: S,
+    compression: Compression,
+    atomic_insert: bool,
+    metrics: MetricsHook,
+    replicas: Replicas,
+    deserialize_error_policy: DeserializeErrorPolicy,
+    max_value_bytes: Option<usize>,
+    scan_count: usize,
+    optimistic_attempts: u32,
+    key_hashing: bool,
+    table_namespaces: bool,
+    reconnect_on_error: bool,
+    invalidation_channel: Option<String>,
+    slow_log_threshold: Option<Duration>,
 }
 
 impl RedisDatabase {
     pub fn new(url: &str, ttl: Duration) -> Result<Self, RedisError> {
-        let client = Client::open(url)
-            .map_err(|e| e.to_string())
+        Self::with_pool_size(url, ttl, DEFAULT_POOL_SIZE)
+    }
+
+    /// Connections are opened lazily, so this does not fail when Redis is unreachable.
+    /// Fails if `max_size` is zero.
+    pub fn with_pool_size(url: &str, ttl: Duration, max_size: u32) -> Result<Self, RedisError> {
+        validate_pool_size(max_size)?;
+        let client = Self::open(url)?;
+        let manager = RedisConnectionManager::new(client, None);
+        Ok(Self::build(manager, Some(ttl), max_size, JsonSerializer))
+    }
+
+    /// Keys are stored without expiry instead of with a default TTL. Explicit TTLs such
+    /// as `insert_with_ttl` still apply.
+    pub fn new_persistent(url: &str) -> Result<Self, RedisError> {
+        let client = Self::open(url)?;
+        let manager = RedisConnectionManager::new(client, None);
+        Ok(Self::build(
+            manager,
+            None,
+            DEFAULT_POOL_SIZE,
+            JsonSerializer,
+        ))
+    }
+
+    /// Wraps a client built by hand, for `redis::ConnectionInfo` options that a URL cannot
+    /// express.
+    pub fn from_client(client: Client, ttl: Duration) -> Self {
+        let manager = RedisConnectionManager::new(client, None);
+        Self::build(manager, Some(ttl), DEFAULT_POOL_SIZE, JsonSerializer)
+    }
+
+    /// Connects over the Unix domain socket at `path`, skipping TCP when Redis runs on the
+    /// same host. Equivalent to a `redis+unix://` URL.
+    #[cfg(unix)]
+    pub fn from_unix_socket(path: impl AsRef<Path>, ttl: Duration) -> Result<Self, RedisError> {
+        let info = ConnectionInfo {
+            addr: ConnectionAddr::Unix(path.as_ref().to_path_buf()),
+            redis: RedisConnectionInfo::default(),
+        };
+        let client = Client::open(info).map_err(RedisError::ConnectionError)?;
+        Ok(Self::from_client(client, ttl))
+    }
+
+    /// Authenticates each new connection as `username`, keeping the credentials out of the
+    /// URL. Any credentials in the URL are replaced.
+    pub fn with_auth(
+        url: &str,
+        username: &str,
+        password: &str,
+        ttl: Duration,
+    ) -> Result<Self, RedisError> {
+        validate_url(url)?;
+        let mut info = url
+            .into_connection_info()
             .map_err(RedisError::ConnectionError)?;
-        Ok(Self { client, ttl })
+        info.redis.username = Some(username.to_string());
+        info.redis.password = Some(password.to_string());
+        let client = Client::open(info).map_err(RedisError::ConnectionError)?;
+        Ok(Self::from_client(client, ttl))
+    }
+
+    /// Bounds both establishing a new connection and waiting for one from the pool.
+    /// Fails if `connect_timeout` is zero.
+    pub fn with_timeout(
+        url: &str,
+        ttl: Duration,
+        connect_timeout: Duration,
+    ) -> Result<Self, RedisError> {
+        validate_connect_timeout(connect_timeout)?;
+        let client = Self::open(url)?;
+        let manager = RedisConnectionManager::new(client, Some(connect_timeout));
+        Ok(Self::build(
+            manager,
+            Some(ttl),
+            DEFAULT_POOL_SIZE,
+            JsonSerializer,
+        ))
+    }
+
+    /// Isolates this database from others sharing the Redis instance by its logical
+    /// database number.
+    pub fn with_db(url: &str, ttl: Duration, db: i64) -> Result<Self, RedisError> {
+        let client = Self::open(url)?;
+        let manager = RedisConnectionManager::new(client, None).with_db(db);
+        Ok(Self::build(
+            manager,
+            Some(ttl),
+            DEFAULT_POOL_SIZE,
+            JsonSerializer,
+        ))
+    }
+
+    /// Connects over TLS, which requires a `rediss://` URL.
+    #[cfg(feature = "tls")]
+    pub fn with_tls(url: &str, ttl: Duration, tls_config: TlsConfig) -> Result<Self, RedisError> {
+        validate_url(url)?;
+        let client = Client::build_with_tls(url, tls_config.into())
+            .map_err(|e| e.to_string())
+            .map_err(RedisError::TlsError)?;
+        let manager = RedisConnectionManager::new(client, None);
+        Ok(Self::build(
+            manager,
+            Some(ttl),
+            DEFAULT_POOL_SIZE,
+            JsonSerializer,
+        ))
+    }
+
+    /// Follows the master of `service_name` as reported by the given Sentinels, so
+    /// connections opened after a failover go to the new master.
+    #[cfg(feature = "sentinel")]
+    pub fn with_sentinel(
+        sentinel_urls: &[&str],
+        service_name: &str,
+        ttl: Duration,
+    ) -> Result<Self, RedisError> {
+        let sentinel =
+            Sentinel::build(sentinel_urls.to_vec()).map_err(RedisError::ConnectionError)?;
+        let manager = RedisConnectionManager::sentinel(sentinel, service_name.to_string(), None);
+        Ok(Self::build(
+            manager,
+            Some(ttl),
+            DEFAULT_POOL_SIZE,
+            JsonSerializer,
+        ))
+    }
+
+    /// Retries commands whose error `is_retryable`.
+    pub fn with_retry(url: &str, ttl: Duration, policy: RetryPolicy) -> Result<Self, RedisError> {
+        Ok(Self {
+            retry: Some(policy),
+            ..Self::new(url, ttl)?
+        })
+    }
+
+    /// Stores every key as `"{prefix}:{key}"`.
+    pub fn with_prefix(url: &str, ttl: Duration, prefix: &str) -> Result<Self, RedisError> {
+        Ok(Self {
+            prefix: Some(prefix.to_string()),
+            ..Self::new(url, ttl)?
+        })
+    }
+
+    /// Values written before compression was enabled are still read back correctly.
+    pub fn with_compression(
+        url: &str,
+        ttl: Duration,
+        compression: Compression,
+    ) -> Result<Self, RedisError> {
+        Ok(Self {
+            compression,
+            ..Self::new(url, ttl)?
+        })
+    }
+
+    /// `StorageRead` operations round-robin across `read_urls`, falling back to
+    /// `write_url` when a replica has no connection available or its connection fails.
+    /// Everything else goes to `write_url`.
+    pub fn with_split(
+        write_url: &str,
+        read_urls: &[&str],
+        ttl: Duration,
+    ) -> Result<Self, RedisError> {
+        let managers = read_urls
+            .iter()
+            .map(|url| Ok(RedisConnectionManager::new(Self::open(url)?, None)))
+            .collect::<Result<Vec<_>, RedisError>>()?;
+        Ok(Self {
+            replicas: Replicas::new(managers),
+            ..Self::new(write_url, ttl)?
+        })
+    }
+
+    pub fn builder() -> RedisDatabaseBuilder {
+        RedisDatabaseBuilder::default()
+    }
+}
+
+impl<S: Serializer> RedisDatabase<S> {
+    pub fn with_serializer(url: &str, ttl: Duration, serializer: S) -> Result<Self, RedisError> {
+        let client = Self::open(url)?;
+        let manager = RedisConnectionManager::new(client, None);
+        Ok(Self::build(
+            manager,
+            Some(ttl),
+            DEFAULT_POOL_SIZE,
+            serializer,
+        ))
+    }
+
+    fn open(url: &str) -> Result<Client, RedisError> {
+        validate_url(url)?;
+        Client::open(url).map_err(RedisError::ConnectionError)
+    }
+
+    fn build(
+        manager: RedisConnectionManager,
+        ttl: Option<Duration>,
+        max_size: u32,
+        serializer: S,
+    ) -> Self {
+        let last_error = LastError::default();
+        let mut builder = Pool::builder()
+            .max_size(max_size)
+            .test_on_check_out(manager.test_on_check_out())
+            .error_handler(Box::new(last_error.clone()));
+        if let Some(connect_timeout) = manager.connect_timeout() {
+            builder = builder.connection_timeout(connect_timeout);
+        }
+        let pool = builder.build_unchecked(manager.clone());
+        Self {
+            pool,
+            manager,
+            last_error,
+            ttl,
+            retry: None,
+            prefix: None,
+            serializer,
+            compression: Compression::None,
+            atomic_insert: true,
+            metrics: MetricsHook::default(),
+            replicas: Replicas::default(),
+            deserialize_error_policy: DeserializeErrorPolicy::default(),
+            max_value_bytes: None,
+            scan_count: SCAN_COUNT,
+            optimistic_attempts: OPTIMISTIC_ATTEMPTS,
+            key_hashing: false,
+            table_namespaces: false,
+            reconnect_on_error: true,
+            invalidation_channel: None,
+            slow_log_threshold: None,
+        }
+    }
+
+    /// The TTL given to written keys, or `None` if they are stored without expiry.
+    pub fn default_ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// The namespace keys are stored under, without the trailing `:`.
+    pub fn prefix(&self) -> Option<&str> {
+        self.prefix.as_deref()
+    }
+
+    pub fn set_prefix(&mut self, prefix: Option<String>) {
+        self.prefix = prefix;
+    }
+
+    pub fn set_metrics(&mut self, metrics: impl Metrics + 'static) {
+        self.metrics = MetricsHook::new(Arc::new(metrics));
+    }
+
+    pub fn set_deserialize_error_policy(&mut self, policy: DeserializeErrorPolicy) {
+        self.deserialize_error_policy = policy;
+    }
+
+    /// Writes whose encoded value exceeds `max_value_bytes` fail with `ValueTooLarge`
+    /// before anything is sent.
+    pub fn set_max_value_bytes(&mut self, max_value_bytes: Option<usize>) {
+        self.max_value_bytes = max_value_bytes;
+    }
+
+    /// The `COUNT` hint for each `SCAN` page, which trades round trips for time spent
+    /// blocking the server.
+    pub fn set_scan_count(&mut self, scan_count: usize) {
+        self.scan_count = scan_count;
+    }
+
+    /// How many times `with_optimistic` runs its closure before giving up with
+    /// `RedisError::Conflict`. Defaults to 5.
+    pub fn set_optimistic_attempts(&mut self, optimistic_attempts: u32) {
+        self.optimistic_attempts = optimistic_attempts;
+    }
+
+    /// Stores each key as the SHA-1 hex digest of the key, after the prefix, so long keys
+    /// such as URLs take a fixed 40 bytes. Lookups hash the key the same way, but hashing
+    /// is one way, so `keys` and `load_all` return the digests rather than the keys.
+    pub fn set_key_hashing(&mut self, key_hashing: bool) {
+        self.key_hashing = key_hashing;
+    }
+
+    /// Stores each table's keys under its own namespace, after the prefix, so tables that
+    /// share a key cannot collide. `FooTable` keys are stored as `FooTable:<key>`, using
+    /// the type name without its module path or generic arguments. `keys` and `load_all`
+    /// then only return the keys of the given table.
+    pub fn set_table_namespaces(&mut self, table_namespaces: bool) {
+        self.table_namespaces = table_namespaces;
+    }
+
+    /// When a command fails because its connection was dropped, it is sent once more on
+    /// another connection before the error is returned. The dropped connection is not put
+    /// back in the pool. Enabled by default, and separate from any `RetryPolicy`.
+    pub fn set_reconnect_on_error(&mut self, reconnect_on_error: bool) {
+        self.reconnect_on_error = reconnect_on_error;
+    }
+
+    /// When set, `StorageWrite::insert` and `StorageWrite::remove` publish the key they
+    /// wrote to `channel`, for an `InvalidationListener` on other nodes.
+    pub fn set_invalidation_channel(&mut self, channel: Option<String>) {
+        self.invalidation_channel = channel;
+    }
+
+    /// Commands that take longer than `threshold`, retries included, are logged as a
+    /// warning with their name, key and duration.
+    pub fn set_slow_log_threshold(&mut self, threshold: Option<Duration>) {
+        self.slow_log_threshold = threshold;
+    }
+
+    /// `insert` uses `SET ... GET` by default, which requires Redis 6.2 or later. When
+    /// disabled, it falls back to a `GET` followed by a separate `SET`.
+    pub fn set_atomic_insert(&mut self, atomic_insert: bool) {
+        self.atomic_insert = atomic_insert;
+    }
+
+    /// Sends `QUIT` on the pooled connections, waiting briefly for any in use, so the
+    /// server does not log them as reset on shutdown. Clones share the pool, so their
+    /// connections are closed too. Errors are ignored, as the server may already be gone.
+    pub fn close(self) {
+        connection_manager::drain(&self.pool);
+        self.replicas.drain();
+    }
+
+    pub fn ping(&self) -> Result<(), RedisError> {
+        let command = Command::ping();
+        let reply = self.execute_command::<String>(command)?;
+        match reply.as_str() {
+            "PONG" => Ok(()),
+            _ => Err(RedisError::QueryError(redis::RedisError::from((
+                ErrorKind::TypeError,
+                "Unexpected PING reply",
+                reply,
+            )))),
+        }
+    }
+
+    /// The server's clock, for expiry math that must agree across clients.
+    pub fn server_time(&self) -> Result<SystemTime, RedisError> {
+        let (secs, micros): (u64, u32) = self.execute_command(Command::time())?;
+        Ok(UNIX_EPOCH + Duration::new(secs, micros * 1_000))
+    }
+
+    pub(crate) fn redis_key<K: RedisKey + ?Sized>(&self, key: &K) -> String {
+        self.key_under(&self.key_prefix(), key)
+    }
+
+    /// Like `redis_key`, but under the table's namespace when table namespaces are on.
+    pub(crate) fn table_key<TableType>(&self, key: &(impl RedisKey + ?Sized)) -> String {
+        self.key_under(&self.table_prefix::<TableType>(), key)
+    }
+
+    fn key_under<K: RedisKey + ?Sized>(&self, prefix: &str, key: &K) -> String {
+        let key = key.to_redis_key();
+        let key = if self.key_hashing {
+            Sha1::from(&key).digest().to_string()
+        } else {
+            key
+        };
+        format!("{prefix}{key}")
+    }
+
+    /// Parses a scanned key back into a table key, without the database prefix.
+    fn parse_key<K: RedisKey>(&self, prefix: &str, key: &str) -> Result<K, RedisError> {
+        K::from_redis_key(key.strip_prefix(prefix).unwrap_or(key)).map_err(|e| match e {
+            RedisError::DeserializeError(_, message) => {
+                RedisError::DeserializeError(key.to_string(), message)
+            }
+            e => e,
+        })
+    }
+
+    fn key_prefix(&self) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{prefix}:"),
+            None => String::new(),
+        }
+    }
+
+    fn table_prefix<TableType>(&self) -> String {
+        let prefix = self.key_prefix();
+        if self.table_namespaces {
+            format!("{prefix}{}:", table_name::<TableType>())
+        } else {
+            prefix
+        }
+    }
+
+    fn connection(&self) -> Result<PooledConnection<RedisConnectionManager>, RedisError> {
+        self.last_error.take();
+        let connection = self.pool.get().map_err(|e| {
+            self.last_error
+                .take()
+                .unwrap_or_else(|| RedisError::TimeoutError(e.to_string()))
+        })?;
+        Ok(connection)
+    }
+
+    /// Runs `f` on one pooled connection, so state such as `WATCH` carries across the
+    /// commands it sends. The connection goes back to the pool afterwards unless `f`
+    /// fails with a timeout or IO error, and failures are not retried.
+    pub fn with_connection<F, R>(&self, f: F) -> Result<R, RedisError>
+    where
+        F: FnOnce(&mut Connection) -> Result<R, RedisError>,
+    {
+        let mut connection = self.connection()?;
+        let result = f(connection.get_mut());
+        if result.as_ref().is_err_and(is_broken_connection) {
+            connection.poison();
+        }
+        result
+    }
+
+    fn run<T, F>(&self, name: &str, operation: F) -> Result<T, RedisError>
+    where
+        F: Fn(&mut RedisConnection) -> redis::RedisResult<T>,
+    {
+        self.run_with(name, |connection| {
+            operation(connection).map_err(query_error)
+        })
+    }
+
+    fn run_with<T, F>(&self, name: &str, operation: F) -> Result<T, RedisError>
+    where
+        F: Fn(&mut RedisConnection) -> Result<T, RedisError>,
+    {
+        self.run_on(name, || self.connection(), operation)
+    }
+
+    fn run_on<T, C, F>(&self, name: &str, connect: C, operation: F) -> Result<T, RedisError>
+    where
+        C: Fn() -> Result<PooledConnection<RedisConnectionManager>, RedisError>,
+        F: Fn(&mut RedisConnection) -> Result<T, RedisError>,
+    {
+        let attempt = || {
+            let start = Instant::now();
+            let mut result = connect().and_then(|mut connection| operation(&mut connection));
+            if self.reconnect_on_error && result.as_ref().is_err_and(is_dropped_connection) {
+                result = connect().and_then(|mut connection| operation(&mut connection));
+            }
+            self.metrics.on_command(name, start.elapsed(), &result);
+            result
+        };
+        match &self.retry {
+            Some(policy) => policy.run(attempt),
+            None => attempt(),
+        }
+    }
+
+    pub(crate) fn execute_pipeline(&self, commands: Vec<Command>) -> Result<(), RedisError> {
+        self.query_pipeline("PIPELINE", redis::pipe(), commands)
+    }
+
+    fn execute_transaction(&self, commands: Vec<Command>) -> Result<(), RedisError> {
+        let mut pipeline = redis::pipe();
+        pipeline.atomic();
+        self.query_pipeline("MULTI", pipeline, commands)
+    }
+
+    fn query_pipeline(
+        &self,
+        name: &str,
+        mut pipeline: redis::Pipeline,
+        commands: Vec<Command>,
+    ) -> Result<(), RedisError> {
+        for command in commands {
+            pipeline.add_command(command.into()).ignore();
+        }
+        self.run(name, |connection| pipeline.query(connection))
+    }
+
+    pub(crate) fn encode<T: Serialize + ?Sized>(
+        &self,
+        key: &str,
+        value: &T,
+    ) -> Result<Vec<u8>, RedisError> {
+        let data = serialize_value(&self.serializer, key, value)?;
+        let data = self.compression.compress(key, data)?;
+        self.check_size(key, &data)?;
+        Ok(data)
+    }
+
+    fn check_size(&self, key: &str, data: &[u8]) -> Result<(), RedisError> {
+        match self.max_value_bytes {
+            Some(limit) if data.len() > limit => Err(RedisError::ValueTooLarge {
+                key: key.to_string(),
+                size: data.len(),
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn decode<T: DeserializeOwned>(&self, key: &str, data: Vec<u8>) -> Result<T, RedisError> {
+        let data = self.compression.decompress(key, data)?;
+        deserialize_bytes(&self.serializer, key, data)
+    }
+
+    fn decode_value<T: DeserializeOwned>(
+        &self,
+        key: &str,
+        data: Option<Vec<u8>>,
+    ) -> Result<Option<T>, RedisError> {
+        data.map(|data| self.decode(key, data)).transpose()
+    }
+
+    /// Runs `command` and refreshes the TTL of `key` in the same `MULTI`/`EXEC`. Without
+    /// a default TTL the command runs on its own.
+    fn execute_with_expire<T: redis::FromRedisValue>(
+        &self,
+        command: Command,
+        key: String,
+    ) -> Result<T, RedisError> {
+        let Some(ttl) = self.ttl else {
+            return self.execute_command(command);
+        };
+        self.execute_then(command, Command::expire(key, ttl))
+    }
+
+    /// Runs `command` and then `expiry` in one `MULTI`, returning the reply to `command`.
+    fn execute_then<T: redis::FromRedisValue>(
+        &self,
+        command: Command,
+        expiry: Command,
+    ) -> Result<T, RedisError> {
+        let mut pipeline = redis::pipe();
+        pipeline
+            .atomic()
+            .add_command(command.into())
+            .add_command(expiry.into())
+            .ignore();
+        let (result,) = self.run("MULTI", |connection| pipeline.query(connection))?;
+        Ok(result)
+    }
+
+    fn _get<T: DeserializeOwned>(&self, key: String) -> Result<Option<T>, RedisError> {
+        let data = self.execute_get(key.clone())?;
+        self.decode_value(&key, data)
+    }
+
+    /// Values are returned in the order of `keys`, with `None` for missing keys and for
+    /// values skipped by the `DeserializeErrorPolicy`.
+    pub fn get_many<TableType>(
+        &self,
+        keys: &[TableType::Key],
+    ) -> Result<Vec<Option<TableType::OwnedValue>>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey + Sized,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let keys: Vec<String> = keys
+            .iter()
+            .map(|key| self.table_key::<TableType>(key))
+            .collect();
+        let command = Command::mget(keys.clone());
+        let data = self.execute_command::<Vec<Option<Vec<u8>>>>(command)?;
+        keys.iter()
+            .zip(data)
+            .map(|(key, data)| {
+                let value = self.decode_value(key, data);
+                self.deserialize_error_policy
+                    .apply(value)
+                    .map(Option::flatten)
+            })
+            .collect()
+    }
+
+    /// Pipelines one `EXISTS` per key, so the results are in the order of `keys`.
+    pub fn exists_many<TableType>(&self, keys: &[TableType::Key]) -> Result<Vec<bool>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey + Sized,
+    {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut pipeline = redis::pipe();
+        for key in keys {
+            pipeline.add_command(Command::exists(self.table_key::<TableType>(key)).into());
+        }
+        self.run("PIPELINE", |connection| pipeline.query(connection))
+    }
+
+    /// Every value is serialized before anything is sent, so a serialization failure writes nothing.
+    pub fn insert_many<TableType>(
+        &mut self,
+        entries: &[(TableType::Key, TableType::Value)],
+    ) -> Result<(), RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey + Sized,
+        TableType::Value: Serialize + Sized,
+    {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let ttl = self.ttl;
+        let commands = entries
+            .iter()
+            .map(|(key, value)| {
+                let key = self.table_key::<TableType>(key);
+                let value = self.encode(&key, value)?;
+                Ok(Command::set(key, value, ttl))
+            })
+            .collect::<Result<Vec<_>, RedisError>>()?;
+        self.execute_pipeline(commands)
+    }
+
+    /// Deletes all `keys` in a single `DEL` and returns how many existed.
+    pub fn remove_many<TableType>(&mut self, keys: &[TableType::Key]) -> Result<u64, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey + Sized,
+    {
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        let keys = keys
+            .iter()
+            .map(|key| self.table_key::<TableType>(key))
+            .collect();
+        let command = Command::delete_many(keys);
+        self.execute_command(command)
+    }
+
+    /// Deletes every key starting with `prefix`, one `SCAN` page at a time, and returns
+    /// how many were removed. `prefix` is relative to the database prefix, if any.
+    pub fn clear_prefix(&mut self, prefix: &str) -> Result<u64, RedisError> {
+        let pattern = format!("{}*", escape_pattern(&self.redis_key(prefix)));
+        let mut cursor = 0;
+        let mut removed = 0;
+        loop {
+            let command = Command::scan(cursor, pattern.clone(), self.scan_count);
+            let (next, keys) = self.execute_command::<(u64, Vec<String>)>(command)?;
+            if !keys.is_empty() {
+                let command = Command::delete_many(keys);
+                removed += self.execute_command::<u64>(command)?;
+            }
+            if next == 0 {
+                return Ok(removed);
+            }
+            cursor = next;
+        }
+    }
+
+    /// Deletes every key in the selected logical database with `FLUSHDB`, ignoring the
+    /// prefix. Other databases on the server are left alone. Meant for resetting an
+    /// isolated test database, never one shared with production data.
+    pub fn flush_db_unsafe(&mut self) -> Result<(), RedisError> {
+        self.execute_command(Command::flush_db())
+    }
+
+    /// Counts the keys under the database prefix with `SCAN`, or the whole database with
+    /// `DBSIZE` when there is no prefix.
+    pub fn len(&self) -> Result<u64, RedisError> {
+        if self.prefix.is_none() {
+            return self.execute_command(Command::db_size());
+        }
+        let pattern = format!("{}*", escape_pattern(&self.key_prefix()));
+        let mut len = 0;
+        for key in Scan::new(self, pattern, self.scan_count) {
+            key?;
+            len += 1;
+        }
+        Ok(len)
+    }
+
+    pub fn is_empty(&self) -> Result<bool, RedisError> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Lazily pages through every key matching the glob `pattern`. Neither the pattern nor
+    /// the returned keys are namespaced with the database prefix.
+    pub fn scan_match(
+        &self,
+        pattern: &str,
+    ) -> impl Iterator<Item = Result<String, RedisError>> + '_ {
+        Scan::new(self, pattern.to_string(), self.scan_count)
+    }
+
+    /// Pages through the keys with `SCAN` as the iterator advances. Keys are returned
+    /// without the database prefix.
+    pub fn keys<TableType>(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<TableType::OwnedKey, RedisError>> + '_, RedisError>
+    where
+        TableType: Table,
+        TableType::OwnedKey: RedisKey,
+    {
+        let prefix = self.table_prefix::<TableType>();
+        let pattern = format!("{}*", escape_pattern(&prefix));
+        let mut scan = Scan::new(self, pattern, self.scan_count);
+        scan.fetch()?;
+        Ok(scan.filter_map(move |key| {
+            let key = key.and_then(|key| self.parse_key(&prefix, &key));
+            self.deserialize_error_policy.apply(key).transpose()
+        }))
+    }
+
+    /// Reads every entry under the database prefix into memory, for warming up small
+    /// tables. Keys are paged through with `SCAN`, and their values fetched with one
+    /// pipelined `MGET` per page. Keys that expire mid-scan are left out.
+    pub fn load_all<TableType>(&self) -> Result<Vec<Entry<TableType>>, RedisError>
+    where
+        TableType: Table,
+        TableType::OwnedKey: RedisKey,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let prefix = self.table_prefix::<TableType>();
+        let pattern = format!("{}*", escape_pattern(&prefix));
+        let mut keys = Scan::new(self, pattern, self.scan_count).collect::<Result<Vec<_>, _>>()?;
+        // `SCAN` may return a key more than once
+        keys.sort_unstable();
+        keys.dedup();
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let pages: Vec<&[String]> = keys.chunks(self.scan_count.max(1)).collect();
+        let mut pipeline = redis::pipe();
+        for page in &pages {
+            pipeline.add_command(Command::mget(page.to_vec()).into());
+        }
+        let values: Vec<Vec<Option<Vec<u8>>>> =
+            self.run("PIPELINE", |connection| pipeline.query(connection))?;
+        let mut entries = Vec::with_capacity(keys.len());
+        for (key, data) in pages
+            .into_iter()
+            .flatten()
+            .zip(values.into_iter().flatten())
+        {
+            let Some(data) = data else {
+                continue;
+            };
+            let entry = self
+                .parse_key(&prefix, key)
+                .and_then(|owned_key| Ok((owned_key, self.decode(key, data)?)));
+            if let Some(entry) = self.deserialize_error_policy.apply(entry)? {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Unlike `transaction`, the queued commands are not applied atomically.
+    pub fn pipeline(&self) -> Pipeline<'_, S> {
+        Pipeline::new(self)
+    }
+
+    /// Sends the commands queued by `f` in a single `MULTI`/`EXEC`. Nothing is sent if
+    /// `f` returns an error.
+    pub fn transaction<F>(&mut self, f: F) -> Result<(), RedisError>
+    where
+        F: FnOnce(&mut Transaction<'_, S>) -> Result<(), RedisError>,
+    {
+        let mut transaction = Transaction::new(self);
+        f(&mut transaction)?;
+        let commands = transaction.into_commands();
+        if commands.is_empty() {
+            return Ok(());
+        }
+        self.execute_transaction(commands)
+    }
+
+    /// An optimistic read-modify-write. `keys` are `WATCH`ed and their raw values passed to
+    /// `f`, and the commands `f` queues are sent in one `MULTI`/`EXEC`. If a watched key
+    /// changes before `EXEC`, `f` runs again on the fresh values, up to the bound set by
+    /// `set_optimistic_attempts`. `keys` are namespaced with the database prefix. With no
+    /// `keys`, nothing is watched and `f` runs once.
+    pub fn with_optimistic<T, F>(&self, keys: &[String], mut f: F) -> Result<T, RedisError>
+    where
+        F: FnMut(&[Option<Vec<u8>>], &mut Transaction<'_, S>) -> Result<T, RedisError>,
+    {
+        if keys.is_empty() {
+            let mut transaction = Transaction::new(self);
+            let value = f(&[], &mut transaction)?;
+            let commands = transaction.into_commands();
+            if !commands.is_empty() {
+                self.execute_transaction(commands)?;
+            }
+            return Ok(value);
+        }
+        let keys: Vec<String> = keys.iter().map(|key| self.redis_key(key)).collect();
+        for _ in 0..self.optimistic_attempts.max(1) {
+            let mut connection = self.connection()?;
+            query::<()>(&mut connection, Command::watch(keys.clone()))?;
+            let mut transaction = Transaction::new(self);
+            let result = query(&mut connection, Command::mget(keys.clone()))
+                .and_then(|values: Vec<Option<Vec<u8>>>| f(&values, &mut transaction));
+            let value = match result {
+                Ok(value) => value,
+                Err(e) => {
+                    let _ = query::<()>(&mut connection, Command::unwatch());
+                    return Err(e);
+                }
+            };
+            let mut pipeline = redis::pipe();
+            pipeline.atomic();
+            for command in transaction.into_commands() {
+                pipeline.add_command(command.into()).ignore();
+            }
+            // EXEC replies with nil when a watched key was modified.
+            let reply: Option<()> = pipeline.query(&mut connection).map_err(query_error)?;
+            if reply.is_some() {
+                return Ok(value);
+            }
+        }
+        Err(RedisError::Conflict(keys.join(", ")))
+    }
+
+    /// Writes `new` only if the current value equals `expected`, where `None` means the
+    /// key is absent. Returns `false` on a mismatch and [`RedisError::Conflict`] if the
+    /// key changed between the comparison and the write.
+    pub fn compare_and_swap<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        expected: Option<&TableType::Value>,
+        new: &TableType::Value,
+    ) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+        TableType::OwnedValue: DeserializeOwned + PartialEq<TableType::Value>,
+    {
+        let key = self.table_key::<TableType>(key);
+        let value = self.encode(&key, new)?;
+        let ttl = self.ttl;
+        self.run_with("WATCH", |connection| {
+            query::<()>(connection, Command::watch(vec![key.clone()]))?;
+            let current = query(connection, Command::get(key.clone()))
+                .and_then(|data| self.decode_value::<TableType::OwnedValue>(&key, data));
+            let matches = match current {
+                Ok(current) => match (current, expected) {
+                    (None, None) => true,
+                    (Some(current), Some(expected)) => current == *expected,
+                    _ => false,
+                },
+                Err(e) => {
+                    let _ = query::<()>(connection, Command::unwatch());
+                    return Err(e);
+                }
+            };
+            if !matches {
+                query::<()>(connection, Command::unwatch())?;
+                return Ok(false);
+            }
+            let command = match expected {
+                Some(_) => Command::update(key.clone(), value.clone(), ttl),
+                None => Command::set_nx(key.clone(), value.clone(), ttl),
+            };
+            let mut pipeline = redis::pipe();
+            pipeline.atomic().add_command(command.into()).ignore();
+            // EXEC replies with nil when the watched key was modified.
+            let result: Option<()> = pipeline.query(connection).map_err(query_error)?;
+            match result {
+                Some(()) => Ok(true),
+                None => Err(RedisError::Conflict(key.clone())),
+            }
+        })
+    }
+
+    /// A missing key counts from zero and is created without an expiry.
+    pub fn increment<TableType>(&mut self, key: &TableType::Key, by: i64) -> Result<i64, RedisError>
+    where
+        TableType: Table<OwnedValue = i64>,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::incr_by(key, by);
+        self.execute_command(command)
+    }
+
+    /// A missing key counts from zero and expires after `window`, which later increments
+    /// leave untouched. This makes a fixed-window rate limiter in one round trip.
+    pub fn increment_with_ttl<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        by: i64,
+        window: Duration,
+    ) -> Result<i64, RedisError>
+    where
+        TableType: Table<OwnedValue = i64>,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::eval(
+            INCREMENT_WITH_TTL.to_string(),
+            vec![key],
+            vec![by.to_string(), window.as_millis().to_string()],
+        );
+        self.execute_command(command)
+    }
+
+    /// A missing key counts from zero and is created without an expiry.
+    pub fn decrement<TableType>(&mut self, key: &TableType::Key, by: i64) -> Result<i64, RedisError>
+    where
+        TableType: Table<OwnedValue = i64>,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::decr_by(key, by);
+        self.execute_command(command)
+    }
+
+    /// Resets the TTL to the default, for sliding expiration, and returns `false` if the
+    /// key is missing. Without a default TTL only the last access time is updated.
+    pub fn touch<TableType>(&mut self, key: &TableType::Key) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = match self.ttl {
+            Some(ttl) => Command::expire(key, ttl),
+            None => Command::touch(key),
+        };
+        self.execute_command(command)
+    }
+
+    /// Returns `false` if the key is missing.
+    pub fn expire<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        ttl: Duration,
+    ) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::expire(key, ttl);
+        self.execute_command(command)
+    }
+
+    /// Expires the key at a wall-clock instant rather than after a duration. Returns
+    /// `false` if the key is missing.
+    pub fn expire_at<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        at: SystemTime,
+    ) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::pexpire_at(key, at);
+        self.execute_command(command)
+    }
+
+    /// Returns `false` if the key is missing or has no expiry.
+    pub fn persist<TableType>(&mut self, key: &TableType::Key) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::persist(key);
+        self.execute_command(command)
+    }
+
+    /// The bytes Redis attributes to the key and its value, or `None` if it is absent.
+    pub fn memory_usage<TableType>(&self, key: &TableType::Key) -> Result<Option<u64>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::memory_usage(key);
+        self.execute_command(command)
+    }
+
+    /// How Redis stores the value internally, such as `listpack` or `hashtable`, or `None`
+    /// if the key is absent.
+    pub fn object_encoding<TableType>(
+        &self,
+        key: &TableType::Key,
+    ) -> Result<Option<String>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::object_encoding(key);
+        self.execute_command(command)
+    }
+
+    /// How long since the key was last read or written, to the second, or `None` if it is
+    /// absent.
+    pub fn object_idle_time<TableType>(
+        &self,
+        key: &TableType::Key,
+    ) -> Result<Option<Duration>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::object_idle_time(key);
+        let seconds: Option<u64> = self.execute_command(command)?;
+        Ok(seconds.map(Duration::from_secs))
+    }
+
+    /// The value in Redis' own serialization format, including its type but not its TTL,
+    /// or `None` if the key is absent.
+    pub fn dump<TableType>(&self, key: &TableType::Key) -> Result<Option<Vec<u8>>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::dump(key);
+        self.execute_command(command)
+    }
+
+    /// Recreates a key from a `dump` payload, possibly taken from another Redis. A zero
+    /// `ttl` restores it without expiry. Fails with `KeyExists` if the key is present.
+    pub fn restore<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        payload: &[u8],
+        ttl: Duration,
+    ) -> Result<(), RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::restore(key.clone(), ttl, payload.to_vec());
+        self.execute_command(command).map_err(|e| match e {
+            RedisError::QueryError(e) if e.code() == Some("BUSYKEY") => RedisError::KeyExists(key),
+            e => e,
+        })
+    }
+
+    /// Fails with `KeyNotFound` if `from` does not exist. An existing `to` is overwritten.
+    pub fn rename<TableType>(
+        &mut self,
+        from: &TableType::Key,
+        to: &TableType::Key,
+    ) -> Result<(), RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let from = self.table_key::<TableType>(from);
+        let command = Command::rename(from.clone(), self.table_key::<TableType>(to));
+        self.execute_command(command)
+            .map_err(|e| key_not_found(e, &from))
+    }
+
+    /// Returns `false` without renaming if `to` already exists.
+    pub fn rename_nx<TableType>(
+        &mut self,
+        from: &TableType::Key,
+        to: &TableType::Key,
+    ) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let from = self.table_key::<TableType>(from);
+        let command = Command::rename_nx(from.clone(), self.table_key::<TableType>(to));
+        self.execute_command(command)
+            .map_err(|e| key_not_found(e, &from))
+    }
+
+    /// Duplicates `from` into `to` on the server, TTL included. Returns `false` if `from`
+    /// is missing, or if `to` exists and `replace` is not set. Requires Redis 6.2 or later.
+    pub fn copy<TableType>(
+        &mut self,
+        from: &TableType::Key,
+        to: &TableType::Key,
+        replace: bool,
+    ) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let command = Command::copy(
+            self.table_key::<TableType>(from),
+            self.table_key::<TableType>(to),
+            replace,
+        );
+        self.execute_command(command)
+    }
+
+    /// The type of value at `key` under the database prefix, or `None` if it is missing.
+    pub fn key_type(&self, key: &str) -> Result<Option<RedisType>, RedisError> {
+        let command = Command::key_type(self.redis_key(key));
+        let reply: String = self.execute_command(command)?;
+        Ok(RedisType::from_reply(&reply))
+    }
+
+    pub fn ttl_status<TableType>(&self, key: &TableType::Key) -> Result<TtlStatus, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        self.execute_ttl(key)
+    }
+
+    pub fn pttl<TableType>(&self, key: &TableType::Key) -> Result<TtlStatus, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        self.execute_pttl(key)
+    }
+
+    /// Reads the value and its `PTTL` in one `MULTI`, so the key cannot expire between the
+    /// two.
+    pub fn get_with_ttl<TableType>(
+        &self,
+        key: &TableType::Key,
+    ) -> Result<Option<(TableType::OwnedValue, TtlStatus)>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = self.table_key::<TableType>(key);
+        let mut pipeline = redis::pipe();
+        pipeline
+            .atomic()
+            .add_command(Command::get(key.clone()).into())
+            .add_command(Command::pttl(key.clone()).into());
+        let (data, millis): (Option<Vec<u8>>, i64) =
+            self.run("MULTI", |connection| pipeline.query(connection))?;
+        let value = self.decode_value(&key, data)?;
+        Ok(value.map(|value| (value, TtlStatus::from_millis(millis))))
+    }
+
+    /// Reads the value and resets its TTL to the default in one `GETEX`, for a sliding
+    /// read-through cache. Without a default TTL this is a plain read. Requires Redis 6.2
+    /// or later.
+    pub fn get_and_refresh<TableType>(
+        &mut self,
+        key: &TableType::Key,
+    ) -> Result<Option<TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::get_ex(key.clone(), self.ttl);
+        let data = self.execute_command::<Option<Vec<u8>>>(command)?;
+        self.decode_value(&key, data)
+    }
+
+    /// Reads and deletes the value in one atomic step, so it can only be taken once. Uses
+    /// `GETDEL` on Redis 6.2 or later, and a `GET` and `DEL` in one `MULTI` otherwise.
+    pub fn take<TableType>(
+        &mut self,
+        key: &TableType::Key,
+    ) -> Result<Option<TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = self.table_key::<TableType>(key);
+        let data = match self
+            .execute_command::<Option<Vec<u8>>>(Command::get_del(key.clone()))
+            .map_err(|e| unsupported_command(e, "GETDEL"))
+        {
+            Err(RedisError::UnsupportedCommand(_)) => {
+                let mut pipeline = redis::pipe();
+                pipeline
+                    .atomic()
+                    .add_command(Command::get(key.clone()).into())
+                    .add_command(Command::delete(key.clone()).into())
+                    .ignore();
+                let (data,) = self.run("MULTI", |connection| pipeline.query(connection))?;
+                data
+            }
+            result => result?,
+        };
+        self.decode_value(&key, data)
+    }
+
+    /// The size in bytes of the stored value, after serialization and compression, without
+    /// fetching it. `STRLEN` reports zero for a missing key, so `EXISTS` is checked in the
+    /// same `MULTI`.
+    pub fn value_len<TableType>(&self, key: &TableType::Key) -> Result<Option<u64>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let mut pipeline = redis::pipe();
+        pipeline
+            .atomic()
+            .add_command(Command::exists(key.clone()).into())
+            .add_command(Command::strlen(key).into());
+        let (exists, len): (bool, u64) =
+            self.run("MULTI", |connection| pipeline.query(connection))?;
+        Ok(exists.then_some(len))
+    }
+
+    /// Writes the value and returns the previous one in a single `SET ... GET`, which
+    /// requires Redis 6.2 or later.
+    pub fn swap<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+    ) -> Result<Option<TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = self.table_key::<TableType>(key);
+        let value = self.encode(&key, value)?;
+        self.set_get(key, value, self.ttl)
+    }
+
+    /// Returns `true` if the key was created and `false` if it already existed.
+    pub fn insert_if_absent<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+    ) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let key = self.table_key::<TableType>(key);
+        let value = self.encode(&key, value)?;
+        let command = Command::set_nx(key, value, self.ttl);
+        // SET NX replies with nil when the key already exists.
+        let reply = self.execute_command::<Option<()>>(command)?;
+        Ok(reply.is_some())
+    }
+
+    /// Returns `None` if the lock is already held. The lock is released when the guard
+    /// is dropped, or after `ttl` if the holder never releases it.
+    pub fn acquire_lock(
+        &self,
+        name: &str,
+        ttl: Duration,
+    ) -> Result<Option<LockGuard<'_, S>>, RedisError> {
+        let key = self.redis_key(&format!("lock:{name}"));
+        let token = lock::token();
+        let command = Command::lock(key.clone(), token.clone(), ttl);
+        // SET NX replies with nil when the lock is already held.
+        let reply = self.execute_command::<Option<()>>(command)?;
+        Ok(reply.map(|()| LockGuard::new(self, key, token)))
+    }
+
+    /// Runs `script` with `EVALSHA`, loading it with `EVAL` the first time the server
+    /// reports `NOSCRIPT`. `keys` are namespaced with the database prefix.
+    pub fn eval<T: redis::FromRedisValue>(
+        &self,
+        script: &str,
+        keys: &[String],
+        args: &[String],
+    ) -> Result<T, RedisError> {
+        let script = Script::new(script);
+        let mut invocation = script.prepare_invoke();
+        for key in keys {
+            invocation.key(self.redis_key(key));
+        }
+        invocation.arg(args);
+        self.run("EVALSHA", |connection| invocation.invoke(connection))
+    }
+
+    /// Returns the number of subscribers that received the message.
+    pub fn publish(&self, channel: &str, message: &str) -> Result<u64, RedisError> {
+        let command = Command::publish(channel.to_string(), message.to_string());
+        self.execute_command(command)
+    }
+
+    /// Subscribes on a dedicated connection outside the pool, which is closed when the
+    /// subscription is dropped.
+    pub fn subscribe(&self, channel: &str) -> Result<Subscription, RedisError> {
+        let connection = self.manager.connect()?.into_inner();
+        Subscription::new(connection, channel)
+    }
+
+    /// Listens for the writes published to `channel`, as set up by
+    /// `set_invalidation_channel`. Like `subscribe`, this holds a dedicated connection.
+    pub fn invalidation_listener(&self, channel: &str) -> Result<InvalidationListener, RedisError> {
+        Ok(InvalidationListener::new(self.subscribe(channel)?))
+    }
+
+    fn publish_invalidation<TableType>(&self, key: &TableType::Key) -> Result<(), RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let Some(channel) = &self.invalidation_channel else {
+            return Ok(());
+        };
+        let event = invalidation::event::<TableType>(&key.to_redis_key());
+        self.publish(channel, &event)?;
+        Ok(())
+    }
+
+    /// On a miss, stores the result of `f` with the default TTL and returns it.
+    pub fn get_or_insert_with<TableType, F>(
+        &mut self,
+        key: &TableType::Key,
+        f: F,
+    ) -> Result<TableType::OwnedValue, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::OwnedValue: Serialize + DeserializeOwned,
+        F: FnOnce() -> TableType::OwnedValue,
+    {
+        self.get_or_try_insert_with::<TableType, _, RedisError>(key, || Ok(f()))
+    }
+
+    /// Like `get_or_insert_with`, but nothing is stored when `f` fails.
+    pub fn get_or_try_insert_with<TableType, F, E>(
+        &mut self,
+        key: &TableType::Key,
+        f: F,
+    ) -> Result<TableType::OwnedValue, E>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::OwnedValue: Serialize + DeserializeOwned,
+        F: FnOnce() -> Result<TableType::OwnedValue, E>,
+        E: From<RedisError>,
+    {
+        let key = self.table_key::<TableType>(key);
+        if let Some(value) = self._get(key.clone())? {
+            return Ok(value);
+        }
+        let value = f()?;
+        let data = self.encode(&key, &value)?;
+        self.execute_set(key, data, self.ttl)?;
+        Ok(value)
+    }
+
+    /// Appends `suffix` to the raw string under `key` and returns its new length in bytes.
+    /// The TTL is left untouched, and a missing key is created without one. The result is
+    /// not valid JSON, so read it back with `get_bytes`.
+    pub fn append<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        suffix: &str,
+    ) -> Result<u64, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::append(key, suffix.to_string());
+        self.execute_command(command)
+    }
+
+    /// Stores `bytes` as is, bypassing both the serializer and compression.
+    pub fn insert_bytes<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        bytes: &[u8],
+    ) -> Result<(), RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        self.check_size(&key, bytes)?;
+        self.execute_set(key, bytes.to_vec(), self.ttl)
+    }
+
+    /// Overwrites the stored bytes from `offset` on, padding with zero bytes if the value is
+    /// shorter, and returns the new length. Like `insert_bytes`, this bypasses the
+    /// serializer, and it refreshes the TTL.
+    pub fn set_range<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        offset: u64,
+        bytes: &[u8],
+    ) -> Result<u64, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::set_range(key.clone(), offset, bytes.to_vec());
+        self.execute_with_expire(command, key)
+    }
+
+    /// The raw bytes from `start` to `end`, both inclusive. Negative offsets count from the
+    /// end of the value, so `-1` is the last byte. A missing key reads as empty.
+    pub fn get_range<TableType>(
+        &self,
+        key: &TableType::Key,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<u8>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        self.execute_command(Command::get_range(key, start, end))
+    }
+
+    /// Reads a value written by `insert_bytes` without deserializing or decompressing it.
+    pub fn get_bytes<TableType>(&self, key: &TableType::Key) -> Result<Option<Vec<u8>>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        self.execute_get(key)
+    }
+
+    pub fn insert_with_ttl<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+        ttl: Duration,
+    ) -> Result<Option<TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        self._insert::<TableType>(key, value, Some(ttl))
+    }
+
+    /// Writes the value, then blocks with `WAIT` until `replicas` replicas acknowledge it
+    /// and returns how many did. Fails with `ReplicationTimeout` if fewer acknowledge
+    /// within `timeout`, although the write has still been applied. As with `WAIT`, a
+    /// zero timeout waits indefinitely.
+    pub fn insert_durable<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+        replicas: usize,
+        timeout: Duration,
+    ) -> Result<usize, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let key = self.table_key::<TableType>(key);
+        let value = self.encode(&key, value)?;
+        let mut pipeline = redis::pipe();
+        pipeline
+            .add_command(Command::set(key.clone(), value, self.ttl).into())
+            .ignore()
+            .add_command(Command::wait(replicas, timeout).into());
+        // `WAIT` only counts writes made on its own connection, so both share one
+        let (acknowledged,): (usize,) =
+            self.run("WAIT", |connection| pipeline.query(connection))?;
+        if acknowledged < replicas {
+            return Err(RedisError::ReplicationTimeout {
+                key,
+                acknowledged,
+                requested: replicas,
+            });
+        }
+        Ok(acknowledged)
+    }
+
+    /// Like `insert_with_ttl`, but the key expires at `expire_at`. An instant in the past
+    /// removes the key straight away.
+    pub fn insert_until<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+        expire_at: SystemTime,
+    ) -> Result<Option<TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = self.table_key::<TableType>(key);
+        let value = self.encode(&key, value)?;
+        let expiry = Command::pexpire_at(key.clone(), expire_at);
+        if self.atomic_insert {
+            let command = Command::set_get(key.clone(), value, None);
+            let data = self.execute_then::<Option<Vec<u8>>>(command, expiry)?;
+            return self.decode_value(&key, data);
+        }
+        let previous = self._get(key.clone())?;
+        let command = Command::set(key, value, None);
+        self.execute_then::<()>(command, expiry)?;
+        Ok(previous)
+    }
+
+    fn _insert<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+        ttl: Option<Duration>,
+    ) -> Result<Option<TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = self.table_key::<TableType>(key);
+        let value = self.encode(&key, value)?;
+        if self.atomic_insert {
+            return self.set_get(key, value, ttl);
+        }
+        let previous = self._get(key.clone())?;
+        self.execute_set(key, value, ttl)?;
+        Ok(previous)
+    }
+
+    fn set_get<T: DeserializeOwned>(
+        &self,
+        key: String,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+    ) -> Result<Option<T>, RedisError> {
+        let command = Command::set_get(key.clone(), value, ttl);
+        let data = self.execute_command::<Option<Vec<u8>>>(command)?;
+        self.decode_value(&key, data)
+    }
+}
+
+/// A key-value pair of a table, as returned by `load_all`.
+type Entry<TableType> = (
+    <TableType as Table>::OwnedKey,
+    <TableType as Table>::OwnedValue,
+);
+
+/// `crate::tables::FooTable<T>` becomes `FooTable`.
+fn table_name<TableType>() -> &'static str {
+    let name = any::type_name::<TableType>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// `redis` marks such a connection as closed, so the pool discards it once it is returned.
+fn is_dropped_connection(error: &RedisError) -> bool {
+    matches!(error, RedisError::ConnectionError(e) if e.is_connection_dropped())
+}
+
+/// Errors after which a reply may still be waiting on the connection.
+fn is_broken_connection(error: &RedisError) -> bool {
+    match error {
+        RedisError::TimeoutError(_) => true,
+        RedisError::ConnectionError(e) => e.is_io_error(),
+        _ => false,
+    }
+}
+
+fn key_not_found(error: RedisError, key: &str) -> RedisError {
+    match error {
+        RedisError::QueryError(e) if e.detail() == Some("no such key") => {
+            RedisError::KeyNotFound(key.to_string())
+        }
+        error => error,
+    }
+}
+
+const URL_SCHEMES: [&str; 4] = ["redis", "rediss", "redis+unix", "unix"];
+
+/// Rejects URLs that `redis` cannot connect to, such as `http://`, with a clearer error
+/// than `Client::open` gives. The rest of the URL is left to `Client::open`.
+pub(crate) fn validate_url(url: &str) -> Result<(), RedisError> {
+    let scheme = url.split_once(':').map_or(url, |(scheme, _)| scheme);
+    if URL_SCHEMES.contains(&scheme) {
+        return Ok(());
+    }
+    Err(RedisError::InvalidUrl(format!(
+        "unsupported scheme \"{scheme}\", expected one of {}",
+        URL_SCHEMES.join(", ")
+    )))
+}
+
+pub(crate) fn validate_pool_size(max_size: u32) -> Result<(), RedisError> {
+    if max_size == 0 {
+        return Err(invalid_config("The pool size must not be zero"));
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_connect_timeout(connect_timeout: Duration) -> Result<(), RedisError> {
+    if connect_timeout.is_zero() {
+        return Err(invalid_config("The connect timeout must not be zero"));
+    }
+    Ok(())
+}
+
+fn invalid_config(message: &'static str) -> RedisError {
+    RedisError::ConnectionError(redis::RedisError::from((
+        ErrorKind::InvalidClientConfig,
+        message,
+    )))
+}
+
+/// Servers that predate a command reply with `ERR unknown command`.
+fn unsupported_command(error: RedisError, name: &str) -> RedisError {
+    match error {
+        RedisError::QueryError(e)
+            if e.code() == Some("ERR")
+                && e.detail().is_some_and(|d| d.starts_with("unknown command")) =>
+        {
+            RedisError::UnsupportedCommand(name.to_string())
+        }
+        error => error,
+    }
+}
+
+fn query<T: redis::FromRedisValue>(
+    connection: &mut RedisConnection,
+    command: Command,
+) -> Result<T, RedisError> {
+    let redis_command: redis::Cmd = command.into();
+    redis_command.query(connection).map_err(query_error)
+}
+
+impl<S> AsRef<RedisDatabase<S>> for RedisDatabase<S> {
+    fn as_ref(&self) -> &RedisDatabase<S> {
+        self
+    }
+}
+
+impl<S: Clone> RedisDatabase<S> {
+    /// A handle sharing this one's connection pool, but writing keys with `ttl`. Combine
+    /// with `set_prefix` to configure tables separately without reconnecting.
+    pub fn with_ttl(&self, ttl: Duration) -> Self {
+        Self {
+            ttl: Some(ttl),
+            ..self.clone()
+        }
+    }
+}
+
+impl<S: Serializer> RedisDatabase<S> {
+    fn read_command<T: redis::FromRedisValue>(&self, command: Command) -> Result<T, RedisError> {
+        if let Some(pool) = self.replicas.next() {
+            let replica = || {
+                pool.get()
+                    .map_err(|e| RedisError::TimeoutError(e.to_string()))
+            };
+            match self.execute_on(&command, replica) {
+                Err(e) if is_broken_connection(&e) => {}
+                result => return result,
+            }
+        }
+        self.execute_on(&command, || self.connection())
+    }
+
+    fn execute_on<T, C>(&self, command: &Command, connect: C) -> Result<T, RedisError>
+    where
+        T: redis::FromRedisValue,
+        C: Fn() -> Result<PooledConnection<RedisConnectionManager>, RedisError>,
+    {
+        let name = command.name();
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "redis",
+            command = name,
+            key = command.key(),
+            outcome = tracing::field::Empty,
+            error = tracing::field::Empty,
+        );
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+        let redis_command: redis::Cmd = command.into();
+        // Arguments include the stored values, so they are only logged in debug builds
+        #[cfg(all(feature = "tracing", debug_assertions))]
+        tracing::debug!(
+            cli = %crate::redis::commands::cli_string(&redis_command),
+            "sending command"
+        );
+        let started = Instant::now();
+        let result = self.run_on(name, connect, |connection| {
+            redis_command.query(connection).map_err(query_error)
+        });
+        if let Some(threshold) = self.slow_log_threshold {
+            let elapsed = started.elapsed();
+            if elapsed > threshold {
+                log::warn!(
+                    "Slow Redis command {name} on key {:?} took {elapsed:?}",
+                    command.key().unwrap_or_default()
+                );
+            }
+        }
+        #[cfg(feature = "tracing")]
+        match &result {
+            Ok(_) => {
+                span.record("outcome", "ok");
+            }
+            Err(e) => {
+                span.record("outcome", "error");
+                span.record("error", e.variant());
+            }
+        }
+        result
+    }
+}
+
+impl<S: Serializer> ExecuteCommand for RedisDatabase<S> {
+    fn execute_command_ref<T: redis::FromRedisValue>(
+        &self,
+        command: &Command,
+    ) -> Result<T, RedisError> {
+        self.execute_on(command, || self.connection())
+    }
+}
+
+impl<S: Serializer> Storage for RedisDatabase<S> {
+    type Error = RedisError;
+}
+
+impl<S, TableType> StorageRead<TableType> for RedisDatabase<S>
+where
+    S: Serializer,
+    TableType: Table,
+    TableType::Key: RedisKey,
+    TableType::OwnedValue: DeserializeOwned,
+{
+    fn get(
+        &self,
+        key: &TableType::Key,
+    ) -> Result<Option<Cow<'_, TableType::OwnedValue>>, Self::Error> {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::get(key.clone());
+        let data = self.read_command::<Option<Vec<u8>>>(command)?;
+        self.decode_value(&key, data)
+    }
+
+    fn exists(&self, key: &TableType::Key) -> Result<bool, Self::Error> {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::exists(key);
+        let data = self.read_command::<bool>(command)?;
+        Ok(data)
+    }
+}
+
+impl<S, TableType> StorageWrite<TableType> for RedisDatabase<S>
+where
+    S: Serializer,
+    TableType: Table,
+    TableType::Key: RedisKey,
+    TableType::Value: Serialize,
+    TableType::OwnedValue: DeserializeOwned,
+{
+    fn insert(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+    ) -> Result<Option<TableType::OwnedValue>, Self::Error> {
+        let ttl = self.ttl;
+        let previous = self._insert::<TableType>(key, value, ttl)?;
+        self.publish_invalidation::<TableType>(key)?;
+        Ok(previous)
+    }
+
+    fn remove(
+        &mut self,
+        key: &TableType::Key,
+    ) -> Result<Option<TableType::OwnedValue>, Self::Error> {
+        let redis_key = self.table_key::<TableType>(key);
+        let previous = self._get(redis_key.clone())?;
+        self.execute_delete(redis_key)?;
+        self.publish_invalidation::<TableType>(key)?;
+        Ok(previous)
+    }
+}
+
+impl<S, TableType> StorageTemp<TableType> for RedisDatabase<S>
+where
+    S: Serializer,
+    TableType: Table,
+    TableType::Key: RedisKey,
+{
+    fn ttl(&self, key: &TableType::Key) -> Result<Duration, Self::Error> {
+        let status = self.ttl_status::<TableType>(key)?;
+        Ok(status.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        borrow::Cow,
+        sync::{Arc, Mutex},
+        time::{Duration, SystemTime},
+    };
+
+    use lushus_storage::{StorageAsMut, StorageAsRef, Table};
+
+    use super::RedisDatabase;
+    #[cfg(feature = "gzip")]
+    use crate::redis::compression::Compression;
+    #[cfg(unix)]
+    use crate::redis::test_support::socket_path;
+    use crate::redis::{
+        connection_manager::query_error,
+        deserialize_error_policy::DeserializeErrorPolicy,
+        error::RedisError,
+        metrics::Metrics,
+        redis_type::RedisType,
+        retry_policy::RetryPolicy,
+        serialization::JsonSerializer,
+        test_support::{unique_key, url},
+        ttl_status::TtlStatus,
+    };
+
+    #[derive(Copy, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Foo {
+        bar: u64,
+    }
+
+    impl Foo {
+        fn new(bar: u64) -> Self {
+            Self { bar }
+        }
+    }
+
+    struct FooTable {}
+
+    impl Table for FooTable {
+        type Key = String;
+        type OwnedKey = Self::Key;
+        type Value = Foo;
+        type OwnedValue = Self::Value;
+    }
+
+    struct CounterTable {}
+
+    impl Table for CounterTable {
+        type Key = String;
+        type OwnedKey = Self::Key;
+        type Value = i64;
+        type OwnedValue = Self::Value;
+    }
+
+    #[test]
+    fn test_constructor() {
+        let url = "redis://localhost:6379";
+        let ttl = Duration::from_secs(1);
+        RedisDatabase::new(url, ttl).expect("Unable to connect to Redis");
+    }
+
+    #[test]
+    fn test_from_client_constructor() {
+        let client = redis::Client::open("redis://localhost:6379").expect("Invalid Redis URL");
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::from_client(client, ttl);
+        assert_eq!(redis.default_ttl(), Some(ttl));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_unix_socket_connects_over_the_socket() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::from_unix_socket(socket_path(), ttl)
+            .expect("Unable to connect to Redis");
+        let key = unique_key("from_unix_socket");
+        let foo = Foo { bar: 42 };
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo)
+            .expect("Failed to insert");
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&key)
+            .expect("Failed to get key")
+            .map(|v| v.into_owned());
+        assert_eq!(ret, Some(foo));
+    }
+
+    #[test]
+    fn test_with_auth_rejects_an_invalid_url() {
+        let ttl = Duration::from_secs(1);
+        let ret = RedisDatabase::with_auth("localhost:6379", "default", "password", ttl);
+        assert!(matches!(ret, Err(RedisError::InvalidUrl(_))));
+    }
+
+    #[test]
+    fn test_new_rejects_an_unsupported_scheme() {
+        let ttl = Duration::from_secs(1);
+        let ret = RedisDatabase::new("http://localhost:6379", ttl);
+        let Err(RedisError::InvalidUrl(message)) = ret else {
+            panic!("Expected an invalid URL error");
+        };
+        assert!(message.contains("\"http\""));
+        assert!(message.contains("redis, rediss, redis+unix"));
+    }
+
+    #[test]
+    fn test_with_auth_authenticates_the_connections() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::with_auth(&url(), "default", "password", ttl)
+            .expect("Invalid Redis URL");
+        redis.ping().expect("Failed to ping Redis");
+    }
+
+    #[test]
+    fn test_getters_report_the_configuration() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::with_prefix("redis://localhost:6379", ttl, "app")
+            .expect("Invalid Redis URL");
+        assert_eq!(redis.default_ttl(), Some(ttl));
+        assert_eq!(redis.prefix(), Some("app"));
+        let redis =
+            RedisDatabase::new_persistent("redis://localhost:6379").expect("Invalid Redis URL");
+        assert_eq!(redis.default_ttl(), None);
+        assert_eq!(redis.prefix(), None);
+    }
+
+    #[test]
+    fn test_with_ttl_only_changes_the_ttl() {
+        let redis =
+            RedisDatabase::with_prefix("redis://localhost:6379", Duration::from_secs(1), "app")
+                .expect("Invalid Redis URL");
+        let ttl = Duration::from_secs(3600);
+        let handle = redis.with_ttl(ttl);
+        assert_eq!(handle.default_ttl(), Some(ttl));
+        assert_eq!(handle.prefix(), Some("app"));
+        assert_eq!(redis.default_ttl(), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_table_namespaces_store_keys_under_the_table_name() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::with_prefix("redis://localhost:6379", ttl, "app")
+            .expect("Invalid Redis URL");
+        assert_eq!(redis.table_key::<FooTable>("key"), "app:key");
+        redis.set_table_namespaces(true);
+        assert_eq!(redis.table_key::<FooTable>("key"), "app:FooTable:key");
+        assert_eq!(redis.redis_key("key"), "app:key");
+    }
+
+    #[test]
+    fn test_table_namespaces_keep_tables_apart() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::with_prefix(&url(), ttl, &unique_key("table_namespaces"))
+            .expect("Unable to connect to Redis");
+        redis.set_table_namespaces(true);
+        let key = "key".to_string();
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &Foo::new(42))
+            .expect("Failed to insert into Redis");
+        redis
+            .storage_as_mut::<CounterTable>()
+            .insert(&key, &7)
+            .expect("Failed to insert into Redis");
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&key)
+            .expect("Failed to get key from Redis");
+        assert_eq!(ret, Some(Cow::Owned(Foo::new(42))));
+        let keys = redis
+            .keys::<CounterTable>()
+            .expect("Failed to scan Redis")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to scan Redis");
+        assert_eq!(keys, vec![key]);
+    }
+
+    #[test]
+    fn test_key_hashing_stores_the_digest_under_the_prefix() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::with_prefix("redis://localhost:6379", ttl, "app")
+            .expect("Invalid Redis URL");
+        redis.set_key_hashing(true);
+        assert_eq!(
+            redis.redis_key("key"),
+            "app:a62f2225bf70bfaccbc7f1ef2a397836717377de"
+        );
+    }
+
+    #[test]
+    fn test_with_pool_size_constructor() {
+        let url = "redis://localhost:6379";
+        let ttl = Duration::from_secs(1);
+        RedisDatabase::with_pool_size(url, ttl, 4).expect("Unable to connect to Redis");
+    }
+
+    #[test]
+    fn test_with_pool_size_rejects_zero() {
+        let ttl = Duration::from_secs(1);
+        let ret = RedisDatabase::with_pool_size("redis://localhost:6379", ttl, 0);
+        assert!(matches!(ret, Err(RedisError::ConnectionError(_))));
+    }
+
+    #[test]
+    fn test_with_timeout_rejects_zero() {
+        let ttl = Duration::from_secs(1);
+        let ret = RedisDatabase::with_timeout("redis://localhost:6379", ttl, Duration::ZERO);
+        assert!(matches!(ret, Err(RedisError::ConnectionError(_))));
+    }
+
+    #[test]
+    fn test_with_timeout_returns_a_timeout_error_when_redis_is_unreachable() {
+        let url = "redis://10.255.255.1:6379";
+        let ttl = Duration::from_secs(1);
+        let connect_timeout = Duration::from_millis(100);
+        let redis =
+            RedisDatabase::with_timeout(url, ttl, connect_timeout).expect("Invalid Redis URL");
+        let key = unique_key("key");
+        let ret = redis.storage_as_ref::<FooTable>().exists(&key);
+        assert!(matches!(ret, Err(RedisError::TimeoutError(_))));
+    }
+
+    #[cfg(feature = "tls")]
+    #[test]
+    fn test_with_tls_rejects_a_non_tls_url() {
+        let ttl = Duration::from_secs(1);
+        let tls_config = crate::redis::tls_config::TlsConfig::new();
+        let ret = RedisDatabase::with_tls("redis://localhost:6379", ttl, tls_config);
+        assert!(matches!(ret, Err(RedisError::TlsError(_))));
+    }
+
+    #[cfg(feature = "sentinel")]
+    #[test]
+    fn test_with_sentinel_requires_at_least_one_sentinel() {
+        let ttl = Duration::from_secs(1);
+        let ret = RedisDatabase::with_sentinel(&[], "mymaster", ttl);
+        assert!(matches!(ret, Err(RedisError::ConnectionError(_))));
+    }
+
+    #[test]
+    fn test_with_retry_reports_the_number_of_attempts() {
+        let url = "redis://10.255.255.1:6379";
+        let ttl = Duration::from_secs(1);
+        let policy = RetryPolicy::new(2, Duration::from_millis(1), Duration::from_millis(1));
+        let mut redis = RedisDatabase::with_timeout(url, ttl, Duration::from_millis(100))
+            .expect("Invalid Redis URL");
+        redis.retry = Some(policy);
+        let key = unique_key("key");
+        let ret = redis.storage_as_ref::<FooTable>().exists(&key);
+        assert!(matches!(ret, Err(RedisError::RetryError(2, _))));
+    }
+
+    #[test]
+    fn test_ping_succeeds_when_redis_is_reachable() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        redis.ping().expect("Failed to ping Redis");
+    }
+
+    #[test]
+    fn test_exists_returns_true_when_the_key_value_is_present() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("key");
+        let foo = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo)
+            .expect("Failed to insert into Redis");
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .exists(&key)
+            .expect("Failed to check key from Redis");
+        assert_eq!(ret, true);
+    }
+
+    #[test]
+    fn test_exists_returns_false_when_the_key_value_is_absent() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("key");
+        let foo = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo)
+            .expect("Failed to insert into Redis");
+        let key = unique_key("bad");
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .exists(&key)
+            .expect("Failed to check key from Redis");
+        assert_eq!(ret, false);
+    }
+
+    #[test]
+    fn test_insert_inserts_the_key_value() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("key");
+        let foo = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo)
+            .expect("Failed to insert into Redis");
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&key)
+            .expect("Failed to get key from Redis");
+        assert_eq!(ret, Some(Cow::Borrowed(&foo)));
+    }
+
+    #[test]
+    fn test_get_many_preserves_the_key_order() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key_a = unique_key("get_many_a");
+        let key_b = unique_key("get_many_b");
+        let missing = unique_key("get_many_missing");
+        let foo_a = Foo::new(42);
+        let foo_b = Foo::new(69);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key_a, &foo_a)
+            .expect("Failed to insert into Redis");
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key_b, &foo_b)
+            .expect("Failed to insert into Redis");
+        let ret = redis
+            .get_many::<FooTable>(&[key_b, missing, key_a])
+            .expect("Failed to get keys from Redis");
+        assert_eq!(ret, vec![Some(foo_b), None, Some(foo_a)]);
+    }
+
+    #[test]
+    fn test_insert_many_inserts_every_key_value() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let foo_a = Foo::new(42);
+        let foo_b = Foo::new(69);
+        let entries = vec![
+            (unique_key("insert_many_a"), foo_a),
+            (unique_key("insert_many_b"), foo_b),
+        ];
+        redis
+            .insert_many::<FooTable>(&entries)
+            .expect("Failed to insert into Redis");
+        let keys: Vec<String> = entries.into_iter().map(|(key, _)| key).collect();
+        let ret = redis
+            .get_many::<FooTable>(&keys)
+            .expect("Failed to get keys from Redis");
+        assert_eq!(ret, vec![Some(foo_a), Some(foo_b)]);
+    }
+
+    #[test]
+    fn test_insert_returns_the_previous_value() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("key");
+        let foo_a = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo_a)
+            .expect("Failed to insert into Redis");
+        let foo_b = Foo::new(69);
+        let prev = redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo_b)
+            .expect("Failed to insert into Redis");
+        assert_eq!(prev, Some(foo_a));
+    }
+
+    #[test]
+    fn test_insert_without_atomic_insert_returns_the_previous_value() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        redis.set_atomic_insert(false);
+        let key = unique_key("non_atomic_insert");
+        let foo_a = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo_a)
+            .expect("Failed to insert into Redis");
+        let foo_b = Foo::new(69);
+        let prev = redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo_b)
+            .expect("Failed to insert into Redis");
+        assert_eq!(prev, Some(foo_a));
+    }
+
+    #[test]
+    fn test_insert_if_absent_does_not_overwrite() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("insert_if_absent");
+        let foo_a = Foo::new(42);
+        let foo_b = Foo::new(69);
+        redis
+            .storage_as_mut::<FooTable>()
+            .remove(&key)
+            .expect("Failed to remove from Redis");
+        let created = redis
+            .insert_if_absent::<FooTable>(&key, &foo_a)
+            .expect("Failed to insert into Redis");
+        assert!(created);
+        let created = redis
+            .insert_if_absent::<FooTable>(&key, &foo_b)
+            .expect("Failed to insert into Redis");
+        assert!(!created);
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&key)
+            .expect("Failed to get key from Redis");
+        assert_eq!(ret, Some(Cow::Borrowed(&foo_a)));
+    }
+
+    #[test]
+    fn test_insert_with_ttl_overrides_the_default_ttl() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("key");
+        let foo = Foo::new(42);
+        let override_ttl = Duration::from_secs(30);
+        redis
+            .insert_with_ttl::<FooTable>(&key, &foo, override_ttl)
+            .expect("Failed to insert into Redis");
+        let value = redis
+            .storage_as_ref::<FooTable>()
+            .ttl(&key)
+            .expect("Failed to get TTL for key");
+        assert_eq!(value, override_ttl);
+    }
+
+    #[test]
+    fn test_insert_with_ttl_accepts_a_sub_second_ttl() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("insert_with_ttl_sub_second");
+        redis
+            .insert_with_ttl::<FooTable>(&key, &Foo::new(42), Duration::from_millis(500))
+            .expect("Failed to insert into Redis");
+        let ttl = redis.pttl::<FooTable>(&key).expect("Failed to get TTL");
+        assert!(matches!(ttl, TtlStatus::Expires(d) if d <= Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_prefixes_isolate_equal_keys() {
+        let ttl = Duration::from_secs(1);
+        let mut redis_a =
+            RedisDatabase::with_prefix(&url(), ttl, "a").expect("Unable to connect to Redis");
+        let mut redis_b =
+            RedisDatabase::with_prefix(&url(), ttl, "b").expect("Unable to connect to Redis");
+        let key = unique_key("key");
+        let foo_a = Foo::new(42);
+        let foo_b = Foo::new(69);
+        redis_a
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo_a)
+            .expect("Failed to insert into Redis");
+        redis_b
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo_b)
+            .expect("Failed to insert into Redis");
+        let ret = redis_a
+            .storage_as_ref::<FooTable>()
+            .get(&key)
+            .expect("Failed to get key from Redis");
+        assert_eq!(ret, Some(Cow::Borrowed(&foo_a)));
+    }
+
+    #[test]
+    fn test_with_serializer_round_trips_the_value() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::with_serializer(&url(), ttl, JsonSerializer)
+            .expect("Unable to connect to Redis");
+        let key = unique_key("with_serializer");
+        let foo = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo)
+            .expect("Failed to insert into Redis");
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&key)
+            .expect("Failed to get key from Redis");
+        assert_eq!(ret, Some(Cow::Borrowed(&foo)));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_with_compression_reads_values_written_without_compression() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let compressed = RedisDatabase::with_compression(&url(), ttl, Compression::Gzip)
+            .expect("Unable to connect to Redis");
+        let key = unique_key("with_compression");
+        let foo = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo)
+            .expect("Failed to insert into Redis");
+        let ret = compressed
+            .storage_as_ref::<FooTable>()
+            .get(&key)
+            .expect("Failed to get key from Redis");
+        assert_eq!(ret, Some(Cow::Borrowed(&foo)));
+    }
+
+    #[test]
+    fn test_clear_prefix_removes_only_matching_keys() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let prefix = unique_key("clear_prefix");
+        let entries = vec![
+            (format!("{prefix}:a"), Foo::new(42)),
+            (format!("{prefix}:b"), Foo::new(69)),
+            (format!("{prefix}_other"), Foo::new(7)),
+        ];
+        redis
+            .insert_many::<FooTable>(&entries)
+            .expect("Failed to insert into Redis");
+        let removed = redis
+            .clear_prefix(&format!("{prefix}:"))
+            .expect("Failed to clear prefix");
+        assert_eq!(removed, 2);
+        let exists = redis
+            .storage_as_ref::<FooTable>()
+            .exists(&entries[2].0)
+            .expect("Failed to check key from Redis");
+        assert!(exists);
+    }
+
+    #[test]
+    fn test_keys_strips_the_database_prefix() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::with_prefix(&url(), ttl, &unique_key("keys"))
+            .expect("Unable to connect to Redis");
+        let entries = vec![
+            ("a".to_string(), Foo::new(42)),
+            ("b".to_string(), Foo::new(69)),
+        ];
+        redis
+            .insert_many::<FooTable>(&entries)
+            .expect("Failed to insert into Redis");
+        let mut keys = redis
+            .keys::<FooTable>()
+            .expect("Failed to scan Redis")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to scan Redis");
+        keys.sort();
+        assert_eq!(keys, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn test_load_all_returns_every_entry_under_the_prefix() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::with_prefix(&url(), ttl, &unique_key("load_all"))
+            .expect("Unable to connect to Redis");
+        redis.set_scan_count(2);
+        let entries = vec![
+            ("a".to_string(), Foo::new(1)),
+            ("b".to_string(), Foo::new(2)),
+            ("c".to_string(), Foo::new(3)),
+        ];
+        redis
+            .insert_many::<FooTable>(&entries)
+            .expect("Failed to insert into Redis");
+        let mut ret = redis
+            .load_all::<FooTable>()
+            .expect("Failed to load from Redis");
+        ret.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(ret, entries);
+    }
+
+    #[test]
+    fn test_transaction_applies_every_command() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key_a = unique_key("transaction_a");
+        let key_b = unique_key("transaction_b");
+        let foo_a = Foo::new(42);
+        let foo_b = Foo::new(69);
+        redis
+            .transaction(|transaction| {
+                transaction.insert::<FooTable>(&key_a, &foo_a)?;
+                transaction.insert::<FooTable>(&key_b, &foo_b)
+            })
+            .expect("Failed to run transaction");
+        let ret = redis
+            .get_many::<FooTable>(&[key_a, key_b])
+            .expect("Failed to get keys from Redis");
+        assert_eq!(ret, vec![Some(foo_a), Some(foo_b)]);
+    }
+
+    #[test]
+    fn test_transaction_sends_nothing_when_the_closure_fails() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("transaction_failed");
+        let ret = redis.transaction(|transaction| {
+            transaction.insert::<FooTable>(&key, &Foo::new(42))?;
+            Err(RedisError::QueryError(redis::RedisError::from((
+                redis::ErrorKind::ClientError,
+                "aborted",
+            ))))
+        });
+        assert!(matches!(ret, Err(RedisError::QueryError(_))));
+        let exists = redis
+            .storage_as_ref::<FooTable>()
+            .exists(&key)
+            .expect("Failed to check key from Redis");
+        assert!(!exists);
+    }
+
+    #[test]
+    fn test_compare_and_swap_only_writes_when_the_value_matches() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("compare_and_swap");
+        let foo_a = Foo::new(42);
+        let foo_b = Foo::new(69);
+        redis
+            .storage_as_mut::<FooTable>()
+            .remove(&key)
+            .expect("Failed to remove from Redis");
+        let swapped = redis
+            .compare_and_swap::<FooTable>(&key, None, &foo_a)
+            .expect("Failed to swap");
+        assert!(swapped);
+        let swapped = redis
+            .compare_and_swap::<FooTable>(&key, Some(&foo_b), &foo_b)
+            .expect("Failed to swap");
+        assert!(!swapped);
+        let swapped = redis
+            .compare_and_swap::<FooTable>(&key, Some(&foo_a), &foo_b)
+            .expect("Failed to swap");
+        assert!(swapped);
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&key)
+            .expect("Failed to get key from Redis");
+        assert_eq!(ret, Some(Cow::Borrowed(&foo_b)));
+    }
+
+    #[test]
+    fn test_with_optimistic_without_keys_sends_nothing_to_watch() {
+        let ttl = Duration::from_secs(1);
+        let redis =
+            RedisDatabase::new("redis://10.255.255.1:6379", ttl).expect("Invalid Redis URL");
+        let ret = redis.with_optimistic(&[], |values, _| {
+            assert!(values.is_empty());
+            Ok(42)
+        });
+        assert_eq!(ret.expect("Failed to run transaction"), 42);
+    }
+
+    #[test]
+    fn test_with_optimistic_without_keys_runs_the_transaction() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("with_optimistic");
+        redis
+            .with_optimistic(&[], |_, transaction| {
+                transaction.insert::<FooTable>(&key, &Foo::new(42))
+            })
+            .expect("Failed to run transaction");
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&key)
+            .expect("Failed to get key from Redis");
+        assert_eq!(ret, Some(Cow::Owned(Foo::new(42))));
+    }
+
+    #[test]
+    fn test_with_optimistic_retries_when_a_watched_key_changes() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let mut other = redis.clone();
+        let key = unique_key("with_optimistic");
+        let mut attempts = 0;
+        let ret = redis.with_optimistic(std::slice::from_ref(&key), |values, transaction| {
+            attempts += 1;
+            assert_eq!(values.len(), 1);
+            if attempts == 1 {
+                other
+                    .storage_as_mut::<FooTable>()
+                    .insert(&key, &Foo::new(1))?;
+            }
+            transaction.insert::<FooTable>(&key, &Foo::new(42))?;
+            Ok(attempts)
+        });
+        assert_eq!(ret.expect("Failed to run transaction"), 2);
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&key)
+            .expect("Failed to get key from Redis");
+        assert_eq!(ret, Some(Cow::Owned(Foo::new(42))));
+    }
+
+    #[test]
+    fn test_get_and_refresh_resets_the_ttl() {
+        let mut redis = RedisDatabase::new(&url(), Duration::from_secs(60))
+            .expect("Unable to connect to Redis");
+        let key = unique_key("get_and_refresh");
+        let foo = Foo::new(42);
+        redis
+            .insert_with_ttl::<FooTable>(&key, &foo, Duration::from_secs(5))
+            .expect("Failed to insert into Redis");
+        let ret = redis
+            .get_and_refresh::<FooTable>(&key)
+            .expect("Failed to get key");
+        assert_eq!(ret, Some(foo));
+        let status = redis
+            .ttl_status::<FooTable>(&key)
+            .expect("Failed to get TTL");
+        assert!(matches!(status, TtlStatus::Expires(d) if d > Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_value_len_reports_the_stored_size() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("value_len");
+        let ret = redis
+            .value_len::<FooTable>(&key)
+            .expect("Failed to get value length");
+        assert_eq!(ret, None);
+        redis
+            .insert_bytes::<FooTable>(&key, b"abcd")
+            .expect("Failed to insert bytes into Redis");
+        let ret = redis
+            .value_len::<FooTable>(&key)
+            .expect("Failed to get value length");
+        assert_eq!(ret, Some(4));
+    }
+
+    #[test]
+    fn test_flush_db_unsafe_only_clears_the_selected_database() {
+        let ttl = Duration::from_secs(10);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let mut isolated =
+            RedisDatabase::with_db(&url(), ttl, 15).expect("Unable to connect to Redis");
+        let key = unique_key("flush_db");
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &Foo::new(42))
+            .expect("Failed to insert into Redis");
+        isolated
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &Foo::new(42))
+            .expect("Failed to insert into Redis");
+        isolated
+            .flush_db_unsafe()
+            .expect("Failed to flush database");
+        assert!(isolated.is_empty().expect("Failed to count keys"));
+        let exists = redis
+            .storage_as_ref::<FooTable>()
+            .exists(&key)
+            .expect("Failed to check key from Redis");
+        assert!(exists);
+    }
+
+    #[test]
+    fn test_take_only_returns_the_value_once() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("take");
+        let foo = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo)
+            .expect("Failed to insert into Redis");
+        let ret = redis.take::<FooTable>(&key).expect("Failed to take key");
+        assert_eq!(ret, Some(foo));
+        let ret = redis.take::<FooTable>(&key).expect("Failed to take key");
+        assert_eq!(ret, None);
+    }
+
+    #[test]
+    fn test_increment_and_decrement_return_the_new_value() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("counter");
+        redis
+            .storage_as_mut::<CounterTable>()
+            .remove(&key)
+            .expect("Failed to remove from Redis");
+        let value = redis
+            .increment::<CounterTable>(&key, 5)
+            .expect("Failed to increment");
+        assert_eq!(value, 5);
+        let value = redis
+            .decrement::<CounterTable>(&key, 2)
+            .expect("Failed to decrement");
+        assert_eq!(value, 3);
+        let ret = redis
+            .storage_as_ref::<CounterTable>()
+            .get(&key)
+            .expect("Failed to get key from Redis");
+        assert_eq!(ret, Some(Cow::Owned(3)));
+    }
+
+    #[test]
+    fn test_increment_with_ttl_only_sets_the_ttl_once() {
+        let mut redis = RedisDatabase::new_persistent(&url()).expect("Unable to connect to Redis");
+        let key = unique_key("rate_limit");
+        let value = redis
+            .increment_with_ttl::<CounterTable>(&key, 1, Duration::from_secs(10))
+            .expect("Failed to increment");
+        assert_eq!(value, 1);
+        let value = redis
+            .increment_with_ttl::<CounterTable>(&key, 1, Duration::from_secs(60))
+            .expect("Failed to increment");
+        assert_eq!(value, 2);
+        let status = redis
+            .ttl_status::<CounterTable>(&key)
+            .expect("Failed to get TTL");
+        assert!(matches!(status, TtlStatus::Expires(d) if d <= Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_expire_replaces_the_ttl() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("expire");
+        let foo = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo)
+            .expect("Failed to insert into Redis");
+        let new_ttl = Duration::from_secs(30);
+        let existed = redis
+            .expire::<FooTable>(&key, new_ttl)
+            .expect("Failed to expire key");
+        assert!(existed);
+        let value = redis
+            .storage_as_ref::<FooTable>()
+            .ttl(&key)
+            .expect("Failed to get TTL for key");
+        assert_eq!(value, new_ttl);
+    }
+
+    #[test]
+    fn test_expire_accepts_a_sub_second_ttl() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("expire_sub_second");
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &Foo::new(42))
+            .expect("Failed to insert into Redis");
+        let existed = redis
+            .expire::<FooTable>(&key, Duration::from_millis(500))
+            .expect("Failed to expire key");
+        assert!(existed);
+        let ttl = redis.pttl::<FooTable>(&key).expect("Failed to get TTL");
+        assert!(matches!(ttl, TtlStatus::Expires(d) if d > Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn test_expire_returns_false_when_the_key_is_absent() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("expire_missing");
+        let existed = redis
+            .expire::<FooTable>(&key, ttl)
+            .expect("Failed to expire key");
+        assert!(!existed);
+    }
+
+    #[test]
+    fn test_expire_at_sets_an_absolute_expiry() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("expire_at");
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &Foo::new(42))
+            .expect("Failed to insert into Redis");
+        let at = SystemTime::now() + Duration::from_secs(30);
+        let existed = redis
+            .expire_at::<FooTable>(&key, at)
+            .expect("Failed to expire key");
+        assert!(existed);
+        let ttl = redis.pttl::<FooTable>(&key).expect("Failed to get TTL");
+        assert!(matches!(ttl, TtlStatus::Expires(d) if d > Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn test_insert_until_an_instant_in_the_past_removes_the_key() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("insert_until_past");
+        let at = SystemTime::now() - Duration::from_secs(1);
+        redis
+            .insert_until::<FooTable>(&key, &Foo::new(42), at)
+            .expect("Failed to insert into Redis");
+        let exists = redis
+            .storage_as_ref::<FooTable>()
+            .exists(&key)
+            .expect("Failed to check key");
+        assert!(!exists);
+    }
+
+    #[test]
+    fn test_persist_removes_the_ttl() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("persist");
+        let foo = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .remove(&key)
+            .expect("Failed to remove from Redis");
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo)
+            .expect("Failed to insert into Redis");
+        let persisted = redis
+            .persist::<FooTable>(&key)
+            .expect("Failed to persist key");
+        assert!(persisted);
+        let status = redis
+            .ttl_status::<FooTable>(&key)
+            .expect("Failed to get TTL for key");
+        assert_eq!(status, TtlStatus::NoExpiry);
+        let persisted = redis
+            .persist::<FooTable>(&key)
+            .expect("Failed to persist key");
+        assert!(!persisted);
+        redis
+            .storage_as_mut::<FooTable>()
+            .remove(&key)
+            .expect("Failed to remove from Redis");
+    }
+
+    #[test]
+    fn test_pttl_returns_millisecond_precision() {
+        let ttl = Duration::from_secs(30);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("pttl");
+        let foo = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo)
+            .expect("Failed to insert into Redis");
+        let value = redis
+            .pttl::<FooTable>(&key)
+            .expect("Failed to get TTL for key")
+            .duration()
+            .expect("Key has no TTL");
+        assert!(value <= ttl && value > ttl - Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_get_with_ttl_returns_the_value_and_its_ttl() {
+        let ttl = Duration::from_secs(30);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("get_with_ttl");
+        let foo = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo)
+            .expect("Failed to insert into Redis");
+        let (value, status) = redis
+            .get_with_ttl::<FooTable>(&key)
+            .expect("Failed to get from Redis")
+            .expect("Key is missing");
+        assert_eq!(value, foo);
+        assert!(
+            matches!(status, TtlStatus::Expires(d) if d <= ttl && d > ttl - Duration::from_secs(1))
+        );
+        let missing = unique_key("get_with_ttl_missing");
+        let ret = redis
+            .get_with_ttl::<FooTable>(&missing)
+            .expect("Failed to get from Redis");
+        assert_eq!(ret, None);
+    }
+
+    #[test]
+    fn test_pttl_reports_a_missing_key() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("pttl_missing");
+        let value = redis
+            .pttl::<FooTable>(&key)
+            .expect("Failed to get TTL for key");
+        assert_eq!(value, TtlStatus::Missing);
+    }
+
+    #[test]
+    fn test_swap_returns_the_previous_value() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("swap");
+        let foo_a = Foo::new(42);
+        let foo_b = Foo::new(69);
+        redis
+            .storage_as_mut::<FooTable>()
+            .remove(&key)
+            .expect("Failed to remove from Redis");
+        let prev = redis
+            .swap::<FooTable>(&key, &foo_a)
+            .expect("Failed to swap");
+        assert_eq!(prev, None);
+        let prev = redis
+            .swap::<FooTable>(&key, &foo_b)
+            .expect("Failed to swap");
+        assert_eq!(prev, Some(foo_a));
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&key)
+            .expect("Failed to get key from Redis");
+        assert_eq!(ret, Some(Cow::Borrowed(&foo_b)));
+    }
+
+    #[test]
+    fn test_acquire_lock_excludes_other_holders_until_released() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let name = unique_key("acquire_lock");
+        let lock = redis
+            .acquire_lock(&name, ttl)
+            .expect("Failed to acquire lock")
+            .expect("Lock is already held");
+        let other = redis
+            .acquire_lock(&name, ttl)
+            .expect("Failed to acquire lock");
+        assert!(other.is_none());
+        let released = lock.release().expect("Failed to release lock");
+        assert!(released);
+        let other = redis
+            .acquire_lock(&name, ttl)
+            .expect("Failed to acquire lock");
+        assert!(other.is_some());
+    }
+
+    #[test]
+    fn test_eval_runs_the_script() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let script = "return KEYS[1] .. ARGV[1]";
+        let key = unique_key("eval");
+        let ret: String = redis
+            .eval(script, std::slice::from_ref(&key), &["42".to_string()])
+            .expect("Failed to run script");
+        assert_eq!(ret, format!("{key}42"));
+    }
+
+    #[test]
+    fn test_eval_reports_script_errors_as_query_errors() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let ret = redis.eval::<()>("return redis.error_reply('boom')", &[], &[]);
+        assert!(matches!(ret, Err(RedisError::QueryError(_))));
+    }
+
+    #[test]
+    fn test_subscribe_receives_published_messages() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let channel = unique_key("subscribe");
+        let mut subscription = redis.subscribe(&channel).expect("Failed to subscribe");
+        let receivers = redis.publish(&channel, "hello").expect("Failed to publish");
+        assert_eq!(receivers, 1);
+        let message = subscription
+            .next()
+            .expect("Subscription ended")
+            .expect("Failed to receive message");
+        assert_eq!(message, "hello");
+    }
+
+    #[test]
+    fn test_metrics_reports_the_command_name() {
+        #[derive(Default)]
+        struct Names(Mutex<Vec<String>>);
+
+        impl Metrics for Arc<Names> {
+            fn on_command(&self, name: &str, _: Duration, _: Result<(), &RedisError>) {
+                self.0.lock().unwrap().push(name.to_string());
+            }
+        }
+
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let names = Arc::new(Names::default());
+        redis.set_metrics(names.clone());
+        redis.ping().expect("Failed to ping Redis");
+        assert_eq!(*names.0.lock().unwrap(), vec!["PING".to_string()]);
+    }
+
+    #[test]
+    fn test_new_persistent_inserts_without_expiry() {
+        let mut redis = RedisDatabase::new_persistent(&url()).expect("Unable to connect to Redis");
+        let key = unique_key("new_persistent");
+        let foo = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo)
+            .expect("Failed to insert into Redis");
+        let status = redis
+            .ttl_status::<FooTable>(&key)
+            .expect("Failed to get TTL for key");
+        assert_eq!(status, TtlStatus::NoExpiry);
+        redis
+            .storage_as_mut::<FooTable>()
+            .remove(&key)
+            .expect("Failed to remove from Redis");
+    }
+
+    #[test]
+    fn test_with_connection_reuses_one_connection() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let ret = redis
+            .with_connection(|connection| {
+                redis::cmd("CLIENT")
+                    .arg("SETNAME")
+                    .arg("with_connection")
+                    .query::<()>(connection)
+                    .map_err(RedisError::QueryError)?;
+                redis::cmd("CLIENT")
+                    .arg("GETNAME")
+                    .query::<Option<String>>(connection)
+                    .map_err(RedisError::QueryError)
+            })
+            .expect("Failed to run commands on one connection");
+        assert_eq!(ret.as_deref(), Some("with_connection"));
+    }
+
+    #[test]
+    fn test_pipeline_applies_every_command() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let kept = unique_key("pipeline_kept");
+        let deleted = unique_key("pipeline_deleted");
+        redis
+            .pipeline()
+            .set::<FooTable>(&kept, &Foo::new(42))
+            .expect("Failed to serialize")
+            .set::<FooTable>(&deleted, &Foo::new(69))
+            .expect("Failed to serialize")
+            .delete::<FooTable>(&deleted)
+            .execute()
+            .expect("Failed to execute pipeline");
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&kept)
+            .expect("Failed to get from Redis");
+        assert_eq!(ret, Some(Cow::Borrowed(&Foo::new(42))));
+        let exists = redis
+            .storage_as_ref::<FooTable>()
+            .exists(&deleted)
+            .expect("Failed to check existence in Redis");
+        assert!(!exists);
+    }
+
+    #[test]
+    fn test_get_or_insert_with_only_computes_on_a_miss() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("get_or_insert_with");
+        redis
+            .storage_as_mut::<FooTable>()
+            .remove(&key)
+            .expect("Failed to remove from Redis");
+        let ret = redis
+            .get_or_insert_with::<FooTable, _>(&key, || Foo::new(42))
+            .expect("Failed to get or insert");
+        assert_eq!(ret, Foo::new(42));
+        let ret = redis
+            .get_or_insert_with::<FooTable, _>(&key, || panic!("Value was not cached"))
+            .expect("Failed to get or insert");
+        assert_eq!(ret, Foo::new(42));
+    }
+
+    #[test]
+    fn test_get_or_try_insert_with_stores_nothing_when_the_closure_fails() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("get_or_try_insert_with");
+        redis
+            .storage_as_mut::<FooTable>()
+            .remove(&key)
+            .expect("Failed to remove from Redis");
+        let ret = redis.get_or_try_insert_with::<FooTable, _, RedisError>(&key, || {
+            Err(RedisError::Conflict(key.clone()))
+        });
+        assert!(matches!(ret, Err(RedisError::Conflict(_))));
+        let exists = redis
+            .storage_as_ref::<FooTable>()
+            .exists(&key)
+            .expect("Failed to check existence in Redis");
+        assert!(!exists);
+    }
+
+    #[test]
+    fn test_get_bytes_returns_the_raw_bytes() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("get_bytes");
+        let bytes = [0x01, 0xff, 0x00, 0x2a];
+        redis
+            .insert_bytes::<FooTable>(&key, &bytes)
+            .expect("Failed to insert bytes into Redis");
+        let ret = redis
+            .get_bytes::<FooTable>(&key)
+            .expect("Failed to get bytes from Redis");
+        assert_eq!(ret.as_deref(), Some(&bytes[..]));
+    }
+
+    #[test]
+    fn test_set_range_overwrites_part_of_the_value() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("set_range");
+        redis
+            .insert_bytes::<FooTable>(&key, b"abcdef")
+            .expect("Failed to insert bytes into Redis");
+        let len = redis
+            .set_range::<FooTable>(&key, 4, b"XYZ")
+            .expect("Failed to set range");
+        assert_eq!(len, 7);
+        let ret = redis
+            .get_bytes::<FooTable>(&key)
+            .expect("Failed to get bytes from Redis");
+        assert_eq!(ret.as_deref(), Some(&b"abcdXYZ"[..]));
+    }
+
+    #[test]
+    fn test_get_range_supports_negative_offsets() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("get_range");
+        redis
+            .insert_bytes::<FooTable>(&key, b"abcdef")
+            .expect("Failed to insert bytes into Redis");
+        let ret = redis
+            .get_range::<FooTable>(&key, 1, 2)
+            .expect("Failed to get range");
+        assert_eq!(ret, b"bc");
+        let ret = redis
+            .get_range::<FooTable>(&key, -3, -1)
+            .expect("Failed to get range");
+        assert_eq!(ret, b"def");
+    }
+
+    #[test]
+    fn test_exists_many_preserves_the_order_of_the_keys() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let present = unique_key("exists_many_present");
+        let missing = unique_key("exists_many_missing");
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&present, &Foo::new(42))
+            .expect("Failed to insert into Redis");
+        redis
+            .storage_as_mut::<FooTable>()
+            .remove(&missing)
+            .expect("Failed to remove from Redis");
+        let ret = redis
+            .exists_many::<FooTable>(&[missing.clone(), present, missing])
+            .expect("Failed to check existence in Redis");
+        assert_eq!(ret, vec![false, true, false]);
     }
 
-    fn connection(&self) -> Result<Connection, RedisError> {
-        let connection = self
-            .client
-            .get_connection()
-            .map_err(|e| e.to_string())
-            .map_err(RedisError::ConnectionError)?;
-        Ok(connection)
+    #[test]
+    fn test_remove_many_counts_the_deleted_keys() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let keys = [unique_key("remove_many_a"), unique_key("remove_many_b")];
+        redis
+            .insert_many::<FooTable>(&[(keys[0].clone(), Foo::new(42))])
+            .expect("Failed to insert into Redis");
+        let removed = redis
+            .remove_many::<FooTable>(&keys)
+            .expect("Failed to remove from Redis");
+        assert_eq!(removed, 1);
+        let exists = redis
+            .exists_many::<FooTable>(&keys)
+            .expect("Failed to check existence in Redis");
+        assert_eq!(exists, vec![false, false]);
     }
 
-    fn _get<T: DeserializeOwned>(&self, key: String) -> Result<Option<T>, RedisError> {
-        let command = Command::get(key.clone());
-        let data = self.execute_command::<Option<String>>(command)?;
-        let value = data
-            .map(|v| serde_json::from_str::<T>(&v))
-            .transpose()
-            .map_err(|e| RedisError::DeserializeError(key, e.to_string()))?;
-        Ok(value)
+    #[test]
+    fn test_rename_moves_the_value() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let from = unique_key("rename_from");
+        let to = unique_key("rename_to");
+        let foo = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&from, &foo)
+            .expect("Failed to insert into Redis");
+        redis
+            .rename::<FooTable>(&from, &to)
+            .expect("Failed to rename key");
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&to)
+            .expect("Failed to get from Redis");
+        assert_eq!(ret, Some(Cow::Borrowed(&foo)));
+        let ret = redis.rename::<FooTable>(&from, &to);
+        assert!(matches!(ret, Err(RedisError::KeyNotFound(key)) if key == from));
     }
-}
 
-impl AsRef<RedisDatabase> for RedisDatabase {
-    fn as_ref(&self) -> &RedisDatabase {
-        self
+    #[test]
+    fn test_rename_nx_keeps_an_existing_target() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let from = unique_key("rename_nx_from");
+        let to = unique_key("rename_nx_to");
+        redis
+            .insert_many::<FooTable>(&[(from.clone(), Foo::new(42)), (to.clone(), Foo::new(69))])
+            .expect("Failed to insert into Redis");
+        let renamed = redis
+            .rename_nx::<FooTable>(&from, &to)
+            .expect("Failed to rename key");
+        assert!(!renamed);
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&to)
+            .expect("Failed to get from Redis");
+        assert_eq!(ret, Some(Cow::Borrowed(&Foo::new(69))));
     }
-}
 
-impl ExecuteCommand for RedisDatabase {
-    fn execute_command<T: redis::FromRedisValue>(&self, command: Command) -> Result<T, RedisError> {
-        let mut connection = self.connection()?;
-        let redis_command: redis::Cmd = command.into();
-        let result = redis_command
-            .query(&mut connection)
-            .map_err(|e| e.to_string())
-            .map_err(RedisError::QueryError)?;
-        Ok(result)
+    #[test]
+    fn test_copy_only_replaces_an_existing_target_when_asked() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let from = unique_key("copy_from");
+        let to = unique_key("copy_to");
+        redis
+            .insert_many::<FooTable>(&[(from.clone(), Foo::new(42)), (to.clone(), Foo::new(69))])
+            .expect("Failed to insert into Redis");
+        let copied = redis
+            .copy::<FooTable>(&from, &to, false)
+            .expect("Failed to copy key");
+        assert!(!copied);
+        let copied = redis
+            .copy::<FooTable>(&from, &to, true)
+            .expect("Failed to copy key");
+        assert!(copied);
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&to)
+            .expect("Failed to get from Redis");
+        assert_eq!(ret, Some(Cow::Borrowed(&Foo::new(42))));
     }
-}
-
-impl Storage for RedisDatabase {
-    type Error = RedisError;
-}
 
-impl<TableType> StorageRead<TableType> for RedisDatabase
-where
-    TableType: Table,
-    TableType::Key: ToString,
-    TableType::OwnedValue: DeserializeOwned,
-{
-    fn get(
-        &self,
-        key: &TableType::Key,
-    ) -> Result<Option<Cow<'_, TableType::OwnedValue>>, Self::Error> {
-        let key = key.to_string();
-        self._get(key)
+    #[test]
+    fn test_key_type_reports_how_the_key_was_written() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let scalar = unique_key("key_type_scalar");
+        let hash = unique_key("key_type_hash");
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&scalar, &Foo::new(42))
+            .expect("Failed to insert into Redis");
+        redis
+            .hash_set::<FooTable>(&hash, "field", &Foo::new(42))
+            .expect("Failed to set hash field");
+        let missing = unique_key("key_type_missing");
+        let ret = redis.key_type(&scalar).expect("Failed to get key type");
+        assert_eq!(ret, Some(RedisType::String));
+        let ret = redis.key_type(&hash).expect("Failed to get key type");
+        assert_eq!(ret, Some(RedisType::Hash));
+        let ret = redis.key_type(&missing).expect("Failed to get key type");
+        assert_eq!(ret, None);
     }
 
-    fn exists(&self, key: &TableType::Key) -> Result<bool, Self::Error> {
-        let key = key.to_string();
-        let command = Command::exists(key);
-        let data = self.execute_command::<bool>(command)?;
-        Ok(data)
+    #[test]
+    fn test_with_split_reads_from_the_replicas() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::with_split(&url(), &[&url(), &url()], ttl)
+            .expect("Unable to connect to Redis");
+        let key = unique_key("with_split");
+        let foo = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo)
+            .expect("Failed to insert into Redis");
+        for _ in 0..3 {
+            let ret = redis
+                .storage_as_ref::<FooTable>()
+                .get(&key)
+                .expect("Failed to get from Redis");
+            assert_eq!(ret, Some(Cow::Borrowed(&foo)));
+        }
     }
-}
 
-impl<TableType> StorageWrite<TableType> for RedisDatabase
-where
-    TableType: Table,
-    TableType::Key: ToString,
-    TableType::Value: Serialize,
-    TableType::OwnedValue: DeserializeOwned,
-{
-    fn insert(
-        &mut self,
-        key: &TableType::Key,
-        value: &TableType::Value,
-    ) -> Result<Option<TableType::OwnedValue>, Self::Error> {
-        let key = key.to_string();
-        let previous = self._get(key.clone())?;
-        let value = serde_json::to_string(value)
-            .map_err(|e| RedisError::SerializeError(key.clone(), e.to_string()))?;
-        let ttl = self.ttl;
-        let command = Command::set(key, value, ttl);
-        self.execute_command(command)?;
-        Ok(previous)
+    #[test]
+    fn test_with_split_reads_from_the_write_endpoint_when_a_replica_is_unreachable() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::with_split(&url(), &["redis://10.255.255.1:6379"], ttl)
+            .expect("Invalid Redis URL");
+        let key = unique_key("with_split_fallback");
+        let foo = Foo::new(42);
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &foo)
+            .expect("Failed to insert into Redis");
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&key)
+            .expect("Failed to get from Redis");
+        assert_eq!(ret, Some(Cow::Borrowed(&foo)));
     }
 
-    fn remove(
-        &mut self,
-        key: &TableType::Key,
-    ) -> Result<Option<TableType::OwnedValue>, Self::Error> {
-        let key = key.to_string();
-        let previous = self._get(key.clone())?;
-        let command = Command::delete(key);
-        self.execute_command(command)?;
-        Ok(previous)
+    #[test]
+    fn test_with_split_falls_back_to_the_write_endpoint() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::with_split(&url(), &["redis://10.255.255.1:6379"], ttl)
+            .expect("Invalid Redis URL");
+        let key = unique_key("with_split_fallback");
+        let exists = redis
+            .storage_as_ref::<FooTable>()
+            .exists(&key)
+            .expect("Failed to check existence in Redis");
+        assert!(!exists);
     }
-}
 
-impl<TableType> StorageTemp<TableType> for RedisDatabase
-where
-    TableType: Table,
-    TableType::Key: ToString,
-{
-    fn ttl(&self, key: &TableType::Key) -> Result<Duration, Self::Error> {
-        let key = key.to_string();
-        let command = Command::ttl(key);
-        let seconds: u64 = self.execute_command(command)?;
-        let duration = Duration::from_secs(seconds);
-        Ok(duration)
+    #[test]
+    fn test_memory_usage_is_none_once_the_key_is_removed() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("memory_usage");
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &Foo::new(42))
+            .expect("Failed to insert into Redis");
+        let usage = redis
+            .memory_usage::<FooTable>(&key)
+            .expect("Failed to get memory usage");
+        assert!(matches!(usage, Some(bytes) if bytes > 0));
+        redis
+            .storage_as_mut::<FooTable>()
+            .remove(&key)
+            .expect("Failed to remove from Redis");
+        let usage = redis
+            .memory_usage::<FooTable>(&key)
+            .expect("Failed to get memory usage");
+        assert_eq!(usage, None);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::{borrow::Cow, time::Duration};
-
-    use lushus_storage::{StorageAsMut, StorageAsRef, Table};
+    #[test]
+    fn test_object_introspection_describes_the_key() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("object");
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &Foo::new(42))
+            .expect("Failed to insert into Redis");
+        let encoding = redis
+            .object_encoding::<FooTable>(&key)
+            .expect("Failed to get object encoding");
+        assert_eq!(encoding.as_deref(), Some("embstr"));
+        let idle = redis
+            .object_idle_time::<FooTable>(&key)
+            .expect("Failed to get object idle time");
+        assert!(matches!(idle, Some(idle) if idle < Duration::from_secs(1)));
+        let missing = unique_key("object_missing");
+        let encoding = redis
+            .object_encoding::<FooTable>(&missing)
+            .expect("Failed to get object encoding");
+        assert_eq!(encoding, None);
+    }
 
-    use super::RedisDatabase;
+    #[test]
+    fn test_len_only_counts_keys_under_the_prefix() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::with_prefix(&url(), ttl, &unique_key("len"))
+            .expect("Unable to connect to Redis");
+        assert!(redis.is_empty().expect("Failed to count keys"));
+        redis
+            .insert_many::<FooTable>(&[
+                ("a".to_string(), Foo::new(1)),
+                ("b".to_string(), Foo::new(2)),
+            ])
+            .expect("Failed to insert into Redis");
+        assert_eq!(redis.len().expect("Failed to count keys"), 2);
+    }
 
-    const URL: &str = "redis://:password@localhost:6379";
+    #[test]
+    fn test_with_db_isolates_the_keys() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::with_db(&url(), ttl, 1).expect("Unable to connect to Redis");
+        let other = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("with_db");
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &Foo::new(42))
+            .expect("Failed to insert into Redis");
+        let exists = redis
+            .storage_as_ref::<FooTable>()
+            .exists(&key)
+            .expect("Failed to check existence in Redis");
+        assert!(exists);
+        let exists = other
+            .storage_as_ref::<FooTable>()
+            .exists(&key)
+            .expect("Failed to check existence in Redis");
+        assert!(!exists);
+    }
 
-    #[derive(Copy, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
-    struct Foo {
-        bar: u64,
+    #[test]
+    fn test_touch_resets_the_ttl() {
+        let ttl = Duration::from_secs(60);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("touch");
+        redis
+            .insert_with_ttl::<FooTable>(&key, &Foo::new(42), Duration::from_secs(1))
+            .expect("Failed to insert into Redis");
+        let touched = redis.touch::<FooTable>(&key).expect("Failed to touch key");
+        assert!(touched);
+        let status = redis
+            .ttl_status::<FooTable>(&key)
+            .expect("Failed to get TTL for key");
+        assert!(matches!(status, TtlStatus::Expires(ttl) if ttl > Duration::from_secs(1)));
+        redis
+            .storage_as_mut::<FooTable>()
+            .remove(&key)
+            .expect("Failed to remove from Redis");
+        let touched = redis.touch::<FooTable>(&key).expect("Failed to touch key");
+        assert!(!touched);
     }
 
-    impl Foo {
-        fn new(bar: u64) -> Self {
-            Self { bar }
-        }
+    #[test]
+    fn test_touch_keeps_keys_with_a_sub_second_ttl() {
+        let ttl = Duration::from_millis(500);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("touch_sub_second");
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &Foo::new(42))
+            .expect("Failed to insert into Redis");
+        let touched = redis.touch::<FooTable>(&key).expect("Failed to touch key");
+        assert!(touched);
+        let ret = redis
+            .storage_as_ref::<FooTable>()
+            .get(&key)
+            .expect("Failed to get key from Redis");
+        assert_eq!(ret, Some(Cow::Owned(Foo::new(42))));
     }
 
-    struct FooTable {}
+    #[test]
+    fn test_get_many_skips_corrupt_values_when_asked() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let skipped = Arc::new(Mutex::new(Vec::new()));
+        redis.set_deserialize_error_policy(DeserializeErrorPolicy::skip_and_log({
+            let skipped = skipped.clone();
+            move |e| skipped.lock().unwrap().push(e.to_string())
+        }));
+        let valid = unique_key("get_many_valid");
+        let corrupt = unique_key("get_many_corrupt");
+        redis
+            .storage_as_mut::<FooTable>()
+            .insert(&valid, &Foo::new(42))
+            .expect("Failed to insert into Redis");
+        redis
+            .insert_bytes::<FooTable>(&corrupt, b"not json")
+            .expect("Failed to insert bytes into Redis");
+        let ret = redis
+            .get_many::<FooTable>(&[corrupt, valid])
+            .expect("Failed to get from Redis");
+        assert_eq!(ret, vec![None, Some(Foo::new(42))]);
+        assert_eq!(skipped.lock().unwrap().len(), 1);
+    }
 
-    impl Table for FooTable {
-        type Key = String;
-        type OwnedKey = Self::Key;
-        type Value = Foo;
-        type OwnedValue = Self::Value;
+    #[test]
+    fn test_append_returns_the_new_length() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("append");
+        redis
+            .insert_bytes::<FooTable>(&key, b"foo")
+            .expect("Failed to insert bytes into Redis");
+        let len = redis
+            .append::<FooTable>(&key, "bar")
+            .expect("Failed to append to value");
+        assert_eq!(len, 6);
+        let ret = redis
+            .get_bytes::<FooTable>(&key)
+            .expect("Failed to get bytes from Redis");
+        assert_eq!(ret.as_deref(), Some(&b"foobar"[..]));
     }
 
     #[test]
-    fn test_constructor() {
-        let url = "redis://localhost:6379";
+    fn test_insert_rejects_values_over_the_size_limit() {
+        let url = "redis://10.255.255.1:6379";
         let ttl = Duration::from_secs(1);
-        RedisDatabase::new(url, ttl).expect("Unable to connect to Redis");
+        let mut redis = RedisDatabase::new(url, ttl).expect("Invalid Redis URL");
+        redis.set_max_value_bytes(Some(8));
+        let key = unique_key("key");
+        let ret = redis
+            .storage_as_mut::<FooTable>()
+            .insert(&key, &Foo::new(42));
+        assert!(matches!(
+            ret,
+            Err(RedisError::ValueTooLarge {
+                size: 10,
+                limit: 8,
+                ..
+            })
+        ));
     }
 
     #[test]
-    fn test_exists_returns_true_when_the_key_value_is_present() {
+    fn test_restore_recreates_a_dumped_key() {
         let ttl = Duration::from_secs(1);
-        let mut redis = RedisDatabase::new(URL, ttl).expect("Unable to connect to Redis");
-        let key = "key".to_string();
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("dump");
         let foo = Foo::new(42);
         redis
             .storage_as_mut::<FooTable>()
             .insert(&key, &foo)
             .expect("Failed to insert into Redis");
+        let payload = redis
+            .dump::<FooTable>(&key)
+            .expect("Failed to dump key")
+            .expect("Key is missing");
+        let ret = redis.restore::<FooTable>(&key, &payload, ttl);
+        assert!(matches!(ret, Err(RedisError::KeyExists(_))));
+        redis
+            .storage_as_mut::<FooTable>()
+            .remove(&key)
+            .expect("Failed to remove from Redis");
+        redis
+            .restore::<FooTable>(&key, &payload, ttl)
+            .expect("Failed to restore key");
         let ret = redis
             .storage_as_ref::<FooTable>()
-            .exists(&key)
-            .expect("Failed to check key from Redis");
-        assert_eq!(ret, true);
+            .get(&key)
+            .expect("Failed to get from Redis");
+        assert_eq!(ret, Some(Cow::Borrowed(&foo)));
     }
 
     #[test]
-    fn test_exists_returns_false_when_the_key_value_is_absent() {
+    fn test_scan_match_returns_the_matching_keys() {
         let ttl = Duration::from_secs(1);
-        let mut redis = RedisDatabase::new(URL, ttl).expect("Unable to connect to Redis");
-        let key = "key".to_string();
-        let foo = Foo::new(42);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        redis.set_scan_count(1);
+        let prefix = unique_key("scan_match");
+        let sessions = vec![format!("{prefix}:1:session"), format!("{prefix}:2:session")];
         redis
-            .storage_as_mut::<FooTable>()
-            .insert(&key, &foo)
+            .insert_many::<FooTable>(&[
+                (sessions[0].clone(), Foo::new(1)),
+                (sessions[1].clone(), Foo::new(2)),
+                (format!("{prefix}:2:profile"), Foo::new(3)),
+            ])
             .expect("Failed to insert into Redis");
-        let key = "bad".to_string();
-        let ret = redis
-            .storage_as_ref::<FooTable>()
-            .exists(&key)
-            .expect("Failed to check key from Redis");
-        assert_eq!(ret, false);
+        let mut keys = redis
+            .scan_match(&format!("{prefix}:*:session"))
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to scan keys");
+        keys.sort();
+        assert_eq!(keys, sessions);
     }
 
     #[test]
-    fn test_insert_inserts_the_key_value() {
+    fn test_close_quits_the_pooled_connections() {
         let ttl = Duration::from_secs(1);
-        let mut redis = RedisDatabase::new(URL, ttl).expect("Unable to connect to Redis");
-        let key = "key".to_string();
-        let foo = Foo::new(42);
+        let redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        redis.ping().expect("Failed to ping Redis");
+        redis.close();
+    }
+
+    #[test]
+    fn test_close_ignores_an_unreachable_server() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::new("redis://localhost:1", ttl).expect("Invalid Redis URL");
+        redis.close();
+    }
+
+    #[test]
+    fn test_server_time_is_close_to_the_local_clock() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let time = redis.server_time().expect("Failed to get the server time");
+        let skew = time
+            .duration_since(SystemTime::now())
+            .unwrap_or_else(|e| e.duration());
+        assert!(skew < Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_command_timeout_fails_slow_commands() {
+        let redis = RedisDatabase::builder()
+            .url(&url())
+            .command_timeout(Duration::from_millis(100))
+            .build()
+            .expect("Invalid Redis URL");
+        let key = unique_key("command_timeout");
+        let ret = redis.with_connection(|connection| {
+            redis::cmd("BLPOP")
+                .arg(&key)
+                .arg(1)
+                .query::<()>(connection)
+                .map_err(query_error)
+        });
+        assert!(matches!(ret, Err(RedisError::TimeoutError(_))));
+        redis.ping().expect("Failed to ping Redis");
+    }
+
+    #[test]
+    fn test_command_timeout_does_not_reuse_the_timed_out_connection() {
+        let mut redis = RedisDatabase::builder()
+            .url(&url())
+            .pool_size(1)
+            .command_timeout(Duration::from_millis(100))
+            .build()
+            .expect("Invalid Redis URL");
+        let key = unique_key("command_timeout");
         redis
             .storage_as_mut::<FooTable>()
-            .insert(&key, &foo)
+            .insert(&key, &Foo::new(42))
             .expect("Failed to insert into Redis");
+        let empty = unique_key("command_timeout");
+        let ret = redis.run("BLPOP", |connection| {
+            redis::cmd("BLPOP")
+                .arg(&empty)
+                .arg(1)
+                .query::<()>(connection)
+        });
+        assert!(matches!(ret, Err(RedisError::TimeoutError(_))));
+        // Let the late nil reply to BLPOP arrive on the timed-out connection
+        std::thread::sleep(Duration::from_millis(1200));
         let ret = redis
             .storage_as_ref::<FooTable>()
             .get(&key)
             .expect("Failed to get key from Redis");
-        assert_eq!(ret, Some(Cow::Borrowed(&foo)));
+        assert_eq!(ret, Some(Cow::Owned(Foo::new(42))));
     }
 
     #[test]
-    fn test_insert_returns_the_previous_value() {
+    fn test_insert_durable_fails_without_enough_replicas() {
         let ttl = Duration::from_secs(1);
-        let mut redis = RedisDatabase::new(URL, ttl).expect("Unable to connect to Redis");
-        let key = "key".to_string();
-        let foo_a = Foo::new(42);
-        redis
-            .storage_as_mut::<FooTable>()
-            .insert(&key, &foo_a)
-            .expect("Failed to insert into Redis");
-        let foo_b = Foo::new(69);
-        let prev = redis
-            .storage_as_mut::<FooTable>()
-            .insert(&key, &foo_b)
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("insert_durable");
+        let timeout = Duration::from_millis(100);
+        let acknowledged = redis
+            .insert_durable::<FooTable>(&key, &Foo::new(42), 0, timeout)
             .expect("Failed to insert into Redis");
-        assert_eq!(prev, Some(foo_a));
+        assert_eq!(acknowledged, 0);
+        let ret = redis.insert_durable::<FooTable>(&key, &Foo::new(42), 1, timeout);
+        assert!(matches!(
+            ret,
+            Err(RedisError::ReplicationTimeout { requested: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_reconnect_on_error_replaces_a_killed_connection() {
+        let ttl = Duration::from_secs(1);
+        let redis =
+            RedisDatabase::with_pool_size(&url(), ttl, 1).expect("Unable to connect to Redis");
+        let id: i64 = redis
+            .with_connection(|connection| {
+                redis::cmd("CLIENT")
+                    .arg("ID")
+                    .query(connection)
+                    .map_err(query_error)
+            })
+            .expect("Failed to get client id");
+        let other = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        other
+            .with_connection(|connection| {
+                redis::cmd("CLIENT")
+                    .arg("KILL")
+                    .arg("ID")
+                    .arg(id)
+                    .query::<()>(connection)
+                    .map_err(query_error)
+            })
+            .expect("Failed to kill client");
+        redis.ping().expect("Failed to ping Redis");
     }
 
     #[test]
     fn test_remove_removes_the_key_value() {
         let ttl = Duration::from_secs(1);
-        let mut redis = RedisDatabase::new(URL, ttl).expect("Unable to connect to Redis");
-        let key = "key".to_string();
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("key");
         let foo = Foo::new(42);
         redis
             .storage_as_mut::<FooTable>()
@@ -263,8 +3694,8 @@ mod tests {
     #[test]
     fn test_remove_returns_the_previous_value() {
         let ttl = Duration::from_secs(1);
-        let mut redis = RedisDatabase::new(URL, ttl).expect("Unable to connect to Redis");
-        let key = "key".to_string();
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("key");
         let foo = Foo::new(42);
         redis
             .storage_as_mut::<FooTable>()
@@ -280,8 +3711,8 @@ mod tests {
     #[test]
     fn test_ttl_returns_the_expected_ttl_value() {
         let ttl = Duration::from_secs(1);
-        let mut redis = RedisDatabase::new(URL, ttl).expect("Unable to connect to Redis");
-        let key = "key".to_string();
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("key");
         let foo = Foo::new(42);
         redis
             .storage_as_mut::<FooTable>()
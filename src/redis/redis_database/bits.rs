@@ -0,0 +1,92 @@
+use lushus_storage::Table;
+
+use crate::redis::{
+    commands::Command, error::RedisError, execute_command::ExecuteCommand,
+    redis_database::RedisDatabase, redis_key::RedisKey, serialization::Serializer,
+};
+
+impl<S: Serializer> RedisDatabase<S> {
+    /// Returns the previous value of the bit. Every write refreshes the TTL of the whole
+    /// bitmap.
+    pub fn set_bit<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        offset: u64,
+        value: bool,
+    ) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::set_bit(key.clone(), offset, value);
+        self.execute_with_expire(command, key)
+    }
+
+    /// Bits past the end of the bitmap, or of a missing key, are unset.
+    pub fn get_bit<TableType>(&self, key: &TableType::Key, offset: u64) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::get_bit(key, offset);
+        self.execute_command(command)
+    }
+
+    /// The number of set bits.
+    pub fn bit_count<TableType>(&self, key: &TableType::Key) -> Result<u64, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::bit_count(key);
+        self.execute_command(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use lushus_storage::Table;
+
+    use crate::redis::{
+        redis_database::RedisDatabase,
+        test_support::{unique_key, url},
+    };
+
+    struct ActivityTable {}
+
+    impl Table for ActivityTable {
+        type Key = String;
+        type OwnedKey = Self::Key;
+        type Value = bool;
+        type OwnedValue = Self::Value;
+    }
+
+    #[test]
+    fn test_set_bit_is_counted_by_bit_count() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("set_bit");
+        for offset in [3, 42, 3] {
+            redis
+                .set_bit::<ActivityTable>(&key, offset, true)
+                .expect("Failed to set bit");
+        }
+        let previous = redis
+            .set_bit::<ActivityTable>(&key, 3, false)
+            .expect("Failed to set bit");
+        assert!(previous);
+        let bit = redis
+            .get_bit::<ActivityTable>(&key, 42)
+            .expect("Failed to get bit");
+        assert!(bit);
+        let count = redis
+            .bit_count::<ActivityTable>(&key)
+            .expect("Failed to count bits");
+        assert_eq!(count, 1);
+    }
+}
@@ -0,0 +1,203 @@
+use std::time::Duration;
+
+use redis::ErrorKind;
+
+use crate::redis::{
+    connection_manager::RedisConnectionManager,
+    error::RedisError,
+    redis_database::{
+        validate_connect_timeout, validate_pool_size, RedisDatabase, DEFAULT_POOL_SIZE,
+    },
+    retry_policy::RetryPolicy,
+    serialization::JsonSerializer,
+};
+
+/// Combines the options of the `RedisDatabase` constructors. Keys are stored without
+/// expiry unless a TTL is set.
+#[derive(Clone, Debug)]
+pub struct RedisDatabaseBuilder {
+    url: Option<String>,
+    ttl: Option<Duration>,
+    prefix: Option<String>,
+    pool_size: u32,
+    connect_timeout: Option<Duration>,
+    command_timeout: Option<Duration>,
+    slow_log_threshold: Option<Duration>,
+    db: Option<i64>,
+    max_value_bytes: Option<usize>,
+    retry: Option<RetryPolicy>,
+    key_hashing: bool,
+    table_namespaces: bool,
+}
+
+impl Default for RedisDatabaseBuilder {
+    fn default() -> Self {
+        Self {
+            url: None,
+            ttl: None,
+            prefix: None,
+            pool_size: DEFAULT_POOL_SIZE,
+            connect_timeout: None,
+            command_timeout: None,
+            slow_log_threshold: None,
+            db: None,
+            max_value_bytes: None,
+            retry: None,
+            key_hashing: false,
+            table_namespaces: false,
+        }
+    }
+}
+
+impl RedisDatabaseBuilder {
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = Some(url.to_string());
+        self
+    }
+
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.to_string());
+        self
+    }
+
+    pub fn pool_size(mut self, pool_size: u32) -> Self {
+        self.pool_size = pool_size;
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Fails a command with `RedisError::TimeoutError` if its reply takes longer than
+    /// `command_timeout`.
+    pub fn command_timeout(mut self, command_timeout: Duration) -> Self {
+        self.command_timeout = Some(command_timeout);
+        self
+    }
+
+    /// See `RedisDatabase::set_slow_log_threshold`.
+    pub fn slow_log_threshold(mut self, slow_log_threshold: Duration) -> Self {
+        self.slow_log_threshold = Some(slow_log_threshold);
+        self
+    }
+
+    pub fn db(mut self, db: i64) -> Self {
+        self.db = Some(db);
+        self
+    }
+
+    pub fn max_value_bytes(mut self, max_value_bytes: usize) -> Self {
+        self.max_value_bytes = Some(max_value_bytes);
+        self
+    }
+
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// See `RedisDatabase::set_key_hashing`.
+    pub fn key_hashing(mut self, key_hashing: bool) -> Self {
+        self.key_hashing = key_hashing;
+        self
+    }
+
+    /// See `RedisDatabase::set_table_namespaces`.
+    pub fn table_namespaces(mut self, table_namespaces: bool) -> Self {
+        self.table_namespaces = table_namespaces;
+        self
+    }
+
+    /// Fails if no URL was given or it cannot be parsed, or if the pool size or connect
+    /// timeout is zero. Like the constructors, this does not connect to Redis.
+    pub fn build(self) -> Result<RedisDatabase, RedisError> {
+        let url = self.url.ok_or_else(|| {
+            RedisError::ConnectionError(redis::RedisError::from((
+                ErrorKind::InvalidClientConfig,
+                "No Redis URL was given",
+            )))
+        })?;
+        validate_pool_size(self.pool_size)?;
+        if let Some(connect_timeout) = self.connect_timeout {
+            validate_connect_timeout(connect_timeout)?;
+        }
+        let client = RedisDatabase::<JsonSerializer>::open(&url)?;
+        let mut manager = RedisConnectionManager::new(client, self.connect_timeout);
+        if let Some(db) = self.db {
+            manager = manager.with_db(db);
+        }
+        if let Some(command_timeout) = self.command_timeout {
+            manager = manager.with_command_timeout(command_timeout);
+        }
+        Ok(RedisDatabase {
+            retry: self.retry,
+            prefix: self.prefix,
+            max_value_bytes: self.max_value_bytes,
+            key_hashing: self.key_hashing,
+            table_namespaces: self.table_namespaces,
+            slow_log_threshold: self.slow_log_threshold,
+            ..RedisDatabase::build(manager, self.ttl, self.pool_size, JsonSerializer)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::redis::{error::RedisError, redis_database::RedisDatabase};
+
+    #[test]
+    fn test_build_requires_a_url() {
+        let ret = RedisDatabase::builder().ttl(Duration::from_secs(1)).build();
+        assert!(matches!(ret, Err(RedisError::ConnectionError(_))));
+    }
+
+    #[test]
+    fn test_build_rejects_a_zero_pool_size() {
+        let ret = RedisDatabase::builder()
+            .url("redis://localhost:6379")
+            .pool_size(0)
+            .build();
+        assert!(matches!(ret, Err(RedisError::ConnectionError(_))));
+    }
+
+    #[test]
+    fn test_build_rejects_a_zero_connect_timeout() {
+        let ret = RedisDatabase::builder()
+            .url("redis://localhost:6379")
+            .connect_timeout(Duration::ZERO)
+            .build();
+        assert!(matches!(ret, Err(RedisError::ConnectionError(_))));
+    }
+
+    #[test]
+    fn test_build_rejects_an_invalid_url() {
+        let ret = RedisDatabase::builder().url("localhost:6379").build();
+        assert!(matches!(ret, Err(RedisError::InvalidUrl(_))));
+    }
+
+    #[test]
+    fn test_build_applies_the_options() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::builder()
+            .url("redis://localhost:6379")
+            .ttl(ttl)
+            .prefix("app")
+            .pool_size(4)
+            .slow_log_threshold(Duration::from_millis(50))
+            .build()
+            .expect("Invalid Redis URL");
+        assert_eq!(redis.default_ttl(), Some(ttl));
+        assert_eq!(redis.prefix(), Some("app"));
+        assert_eq!(redis.slow_log_threshold, Some(Duration::from_millis(50)));
+        assert_eq!(redis.redis_key("key"), "app:key");
+    }
+}
@@ -0,0 +1,258 @@
+use std::{collections::HashMap, time::Duration};
+
+use lushus_storage::Table;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::redis::{
+    commands::Command,
+    error::RedisError,
+    execute_command::ExecuteCommand,
+    redis_database::{unsupported_command, RedisDatabase},
+    redis_key::RedisKey,
+    scan::Scan,
+    serialization::Serializer,
+    ttl_status::TtlStatus,
+};
+
+impl<S: Serializer> RedisDatabase<S> {
+    /// Every write refreshes the TTL of the whole hash. Returns `true` if the field is new.
+    pub fn hash_set<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        field: &str,
+        value: &TableType::Value,
+    ) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let key = self.table_key::<TableType>(key);
+        let value = self.encode(&key, value)?;
+        let command = Command::hset(key.clone(), field.to_string(), value);
+        self.execute_with_expire(command, key)
+    }
+
+    /// Like `hash_set`, but the field also expires on its own after `ttl`. Requires Redis
+    /// 7.4 or later, and fails with `RedisError::UnsupportedCommand` on older servers.
+    pub fn hash_set_with_ttl<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        field: &str,
+        value: &TableType::Value,
+        ttl: Duration,
+    ) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let key = self.table_key::<TableType>(key);
+        let value = self.encode(&key, value)?;
+        let mut pipeline = redis::pipe();
+        pipeline
+            .atomic()
+            .add_command(Command::hset(key.clone(), field.to_string(), value).into())
+            .add_command(Command::hexpire(key.clone(), field.to_string(), ttl).into())
+            .ignore();
+        if let Some(ttl) = self.ttl {
+            pipeline
+                .add_command(Command::expire(key, ttl).into())
+                .ignore();
+        }
+        let (created,) = self
+            .run("MULTI", |connection| pipeline.query(connection))
+            .map_err(|e| unsupported_command(e, "HPEXPIRE"))?;
+        Ok(created)
+    }
+
+    /// The remaining TTL of a single field. Requires Redis 7.4 or later.
+    pub fn hash_field_ttl<TableType>(
+        &self,
+        key: &TableType::Key,
+        field: &str,
+    ) -> Result<TtlStatus, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let command = Command::httl(self.table_key::<TableType>(key), field.to_string());
+        let reply: Vec<i64> = self
+            .execute_command(command)
+            .map_err(|e| unsupported_command(e, "HTTL"))?;
+        Ok(reply
+            .first()
+            .map_or(TtlStatus::Missing, |&reply| TtlStatus::from_secs(reply)))
+    }
+
+    pub fn hash_get<TableType>(
+        &self,
+        key: &TableType::Key,
+        field: &str,
+    ) -> Result<Option<TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::hget(key.clone(), field.to_string());
+        let data = self.execute_command::<Option<Vec<u8>>>(command)?;
+        data.map(|data| self.decode(&key, data)).transpose()
+    }
+
+    pub fn hash_get_all<TableType>(
+        &self,
+        key: &TableType::Key,
+    ) -> Result<HashMap<String, TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::hget_all(key.clone());
+        let data = self.execute_command::<HashMap<String, Vec<u8>>>(command)?;
+        data.into_iter()
+            .map(|(field, data)| Ok((field, self.decode(&key, data)?)))
+            .collect()
+    }
+
+    /// Lazily pages through the fields with `HSCAN`, so large hashes are read without
+    /// blocking the server the way `HGETALL` does. A field may be returned more than once.
+    pub fn hash_scan<TableType>(
+        &self,
+        key: &TableType::Key,
+    ) -> impl Iterator<Item = Result<(String, TableType::OwnedValue), RedisError>> + '_
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = self.table_key::<TableType>(key);
+        let count = self.scan_count;
+        let pages = Scan::<_, (String, Vec<u8>)>::with_command(self, {
+            let key = key.clone();
+            move |cursor| Command::hscan(key.clone(), cursor, count)
+        });
+        pages.map(move |field| {
+            let (field, data) = field?;
+            let value = self.decode(&key, data).map_err(|e| match e {
+                RedisError::DeserializeError(key, message) => {
+                    RedisError::DeserializeError(key, format!("field \"{field}\": {message}"))
+                }
+                e => e,
+            })?;
+            Ok((field, value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use lushus_storage::Table;
+
+    use crate::redis::{
+        error::RedisError,
+        redis_database::RedisDatabase,
+        test_support::{unique_key, url},
+        ttl_status::TtlStatus,
+    };
+
+    struct FieldTable {}
+
+    impl Table for FieldTable {
+        type Key = String;
+        type OwnedKey = Self::Key;
+        type Value = u64;
+        type OwnedValue = Self::Value;
+    }
+
+    #[test]
+    fn test_hash_get_all_returns_every_field() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("hash_get_all");
+        redis
+            .hash_set::<FieldTable>(&key, "a", &42)
+            .expect("Failed to set hash field");
+        redis
+            .hash_set::<FieldTable>(&key, "b", &69)
+            .expect("Failed to set hash field");
+        let ret = redis
+            .hash_get::<FieldTable>(&key, "a")
+            .expect("Failed to get hash field");
+        assert_eq!(ret, Some(42));
+        let ret = redis
+            .hash_get_all::<FieldTable>(&key)
+            .expect("Failed to get hash");
+        let expected = HashMap::from([("a".to_string(), 42), ("b".to_string(), 69)]);
+        assert_eq!(ret, expected);
+    }
+
+    #[test]
+    fn test_hash_set_with_ttl_expires_the_field() {
+        let ttl = Duration::from_secs(10);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("hash_set_with_ttl");
+        redis
+            .hash_set::<FieldTable>(&key, "a", &42)
+            .expect("Failed to set hash field");
+        let ret = redis.hash_set_with_ttl::<FieldTable>(&key, "b", &69, Duration::from_secs(5));
+        if matches!(ret, Err(RedisError::UnsupportedCommand(_))) {
+            // The server predates Redis 7.4
+            return;
+        }
+        assert!(ret.expect("Failed to set hash field"));
+        let status = redis
+            .hash_field_ttl::<FieldTable>(&key, "b")
+            .expect("Failed to get field TTL");
+        assert!(matches!(status, TtlStatus::Expires(d) if d > Duration::from_secs(3)));
+        let status = redis
+            .hash_field_ttl::<FieldTable>(&key, "a")
+            .expect("Failed to get field TTL");
+        assert_eq!(status, TtlStatus::NoExpiry);
+    }
+
+    #[test]
+    fn test_hash_set_with_ttl_keeps_a_field_with_a_sub_second_ttl() {
+        let ttl = Duration::from_secs(10);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("hash_set_with_ttl");
+        let ret = redis.hash_set_with_ttl::<FieldTable>(&key, "a", &42, Duration::from_millis(500));
+        if matches!(ret, Err(RedisError::UnsupportedCommand(_))) {
+            // The server predates Redis 7.4
+            return;
+        }
+        assert!(ret.expect("Failed to set hash field"));
+        let status = redis
+            .hash_field_ttl::<FieldTable>(&key, "a")
+            .expect("Failed to get field TTL");
+        assert!(matches!(status, TtlStatus::Expires(_)));
+    }
+
+    #[test]
+    fn test_hash_scan_pages_through_every_field() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        redis.set_scan_count(1);
+        let key = unique_key("hash_scan");
+        for (field, value) in [("a", 42), ("b", 69), ("c", 7)] {
+            redis
+                .hash_set::<FieldTable>(&key, field, &value)
+                .expect("Failed to set hash field");
+        }
+        let ret = redis
+            .hash_scan::<FieldTable>(&key)
+            .collect::<Result<HashMap<_, _>, _>>()
+            .expect("Failed to scan hash");
+        let expected = HashMap::from([
+            ("a".to_string(), 42),
+            ("b".to_string(), 69),
+            ("c".to_string(), 7),
+        ]);
+        assert_eq!(ret, expected);
+    }
+}
@@ -0,0 +1,103 @@
+use lushus_storage::Table;
+use serde::Serialize;
+
+use crate::redis::{
+    commands::Command, error::RedisError, execute_command::ExecuteCommand,
+    redis_database::RedisDatabase, redis_key::RedisKey, serialization::Serializer,
+};
+
+impl<S: Serializer> RedisDatabase<S> {
+    /// Returns `true` if the estimated cardinality changed. Every addition refreshes the
+    /// TTL of the whole HyperLogLog.
+    pub fn pfadd<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        element: &TableType::Value,
+    ) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let key = self.table_key::<TableType>(key);
+        let element = self.encode(&key, element)?;
+        let command = Command::pfadd(key.clone(), element);
+        self.execute_with_expire(command, key)
+    }
+
+    /// The approximate number of distinct elements added, with a standard error of 0.81%.
+    pub fn pfcount<TableType>(&self, key: &TableType::Key) -> Result<u64, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::pfcount(key);
+        self.execute_command(command)
+    }
+
+    /// Stores the union of `sources` and any existing `destination` in `destination`.
+    pub fn pfmerge<TableType>(
+        &mut self,
+        destination: &TableType::Key,
+        sources: &[TableType::Key],
+    ) -> Result<(), RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey + Sized,
+    {
+        let destination = self.table_key::<TableType>(destination);
+        let sources = sources
+            .iter()
+            .map(|key| self.table_key::<TableType>(key))
+            .collect();
+        let command = Command::pfmerge(destination.clone(), sources);
+        self.execute_with_expire(command, destination)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use lushus_storage::Table;
+
+    use crate::redis::{
+        redis_database::RedisDatabase,
+        test_support::{unique_key, url},
+    };
+
+    struct VisitorTable {}
+
+    impl Table for VisitorTable {
+        type Key = String;
+        type OwnedKey = Self::Key;
+        type Value = String;
+        type OwnedValue = Self::Value;
+    }
+
+    #[test]
+    fn test_pfmerge_counts_the_union() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let monday = unique_key("pfadd_monday");
+        let tuesday = unique_key("pfadd_tuesday");
+        let week = unique_key("pfmerge_week");
+        for (key, visitor) in [(&monday, "alice"), (&monday, "bob"), (&tuesday, "alice")] {
+            redis
+                .pfadd::<VisitorTable>(key, &visitor.to_string())
+                .expect("Failed to add to HyperLogLog");
+        }
+        let count = redis
+            .pfcount::<VisitorTable>(&monday)
+            .expect("Failed to count HyperLogLog");
+        assert_eq!(count, 2);
+        redis
+            .pfmerge::<VisitorTable>(&week, &[monday, tuesday])
+            .expect("Failed to merge HyperLogLogs");
+        let count = redis
+            .pfcount::<VisitorTable>(&week)
+            .expect("Failed to count HyperLogLog");
+        assert_eq!(count, 2);
+    }
+}
@@ -0,0 +1,104 @@
+use lushus_storage::Table;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::redis::{
+    commands::Command, error::RedisError, execute_command::ExecuteCommand,
+    redis_database::RedisDatabase, redis_key::RedisKey, serialization::Serializer,
+};
+
+impl<S: Serializer> RedisDatabase<S> {
+    /// Appends to the back of the list and returns its new length. Every push refreshes
+    /// the TTL of the whole list.
+    pub fn list_push<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+    ) -> Result<u64, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let key = self.table_key::<TableType>(key);
+        let value = self.encode(&key, value)?;
+        let command = Command::rpush(key.clone(), value);
+        self.execute_with_expire(command, key)
+    }
+
+    /// Removes from the front of the list, so elements come out in the order they were pushed.
+    pub fn list_pop<TableType>(
+        &mut self,
+        key: &TableType::Key,
+    ) -> Result<Option<TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::lpop(key.clone());
+        let data = self.execute_command::<Option<Vec<u8>>>(command)?;
+        data.map(|data| self.decode(&key, data)).transpose()
+    }
+
+    pub fn list_len<TableType>(&self, key: &TableType::Key) -> Result<u64, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::llen(key);
+        self.execute_command(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use lushus_storage::Table;
+
+    use crate::redis::{
+        redis_database::RedisDatabase,
+        test_support::{unique_key, url},
+    };
+
+    struct QueueTable {}
+
+    impl Table for QueueTable {
+        type Key = String;
+        type OwnedKey = Self::Key;
+        type Value = u64;
+        type OwnedValue = Self::Value;
+    }
+
+    #[test]
+    fn test_list_pop_returns_elements_in_push_order() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("list_pop");
+        redis
+            .list_push::<QueueTable>(&key, &42)
+            .expect("Failed to push onto list");
+        let len = redis
+            .list_push::<QueueTable>(&key, &69)
+            .expect("Failed to push onto list");
+        assert_eq!(len, 2);
+        let ret = redis
+            .list_pop::<QueueTable>(&key)
+            .expect("Failed to pop from list");
+        assert_eq!(ret, Some(42));
+        let len = redis
+            .list_len::<QueueTable>(&key)
+            .expect("Failed to get list length");
+        assert_eq!(len, 1);
+        let ret = redis
+            .list_pop::<QueueTable>(&key)
+            .expect("Failed to pop from list");
+        assert_eq!(ret, Some(69));
+        let ret = redis
+            .list_pop::<QueueTable>(&key)
+            .expect("Failed to pop from list");
+        assert_eq!(ret, None);
+    }
+}
@@ -0,0 +1,134 @@
+use lushus_storage::Table;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::redis::{
+    commands::Command, error::RedisError, execute_command::ExecuteCommand,
+    redis_database::RedisDatabase, redis_key::RedisKey, serialization::Serializer,
+};
+
+impl<S: Serializer> RedisDatabase<S> {
+    /// Returns `true` if the member was not already in the set. Every addition refreshes
+    /// the TTL of the whole set.
+    pub fn set_add<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        member: &TableType::Value,
+    ) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let key = self.table_key::<TableType>(key);
+        let member = self.encode(&key, member)?;
+        let command = Command::sadd(key.clone(), member);
+        self.execute_with_expire(command, key)
+    }
+
+    /// Returns `true` if the member was in the set.
+    pub fn set_remove<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        member: &TableType::Value,
+    ) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let key = self.table_key::<TableType>(key);
+        let member = self.encode(&key, member)?;
+        let command = Command::srem(key, member);
+        self.execute_command(command)
+    }
+
+    pub fn set_is_member<TableType>(
+        &self,
+        key: &TableType::Key,
+        member: &TableType::Value,
+    ) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let key = self.table_key::<TableType>(key);
+        let member = self.encode(&key, member)?;
+        let command = Command::sismember(key, member);
+        self.execute_command(command)
+    }
+
+    /// Members are returned in no particular order.
+    pub fn set_members<TableType>(
+        &self,
+        key: &TableType::Key,
+    ) -> Result<Vec<TableType::OwnedValue>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::smembers(key.clone());
+        let data = self.execute_command::<Vec<Vec<u8>>>(command)?;
+        data.into_iter()
+            .map(|data| self.decode(&key, data))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use lushus_storage::Table;
+
+    use crate::redis::{
+        redis_database::RedisDatabase,
+        test_support::{unique_key, url},
+    };
+
+    struct MemberTable {}
+
+    impl Table for MemberTable {
+        type Key = String;
+        type OwnedKey = Self::Key;
+        type Value = u64;
+        type OwnedValue = Self::Value;
+    }
+
+    #[test]
+    fn test_set_members_returns_each_member_once() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("set_members");
+        for member in [42, 69, 42] {
+            redis
+                .set_add::<MemberTable>(&key, &member)
+                .expect("Failed to add to set");
+        }
+        let mut ret = redis
+            .set_members::<MemberTable>(&key)
+            .expect("Failed to get set members");
+        ret.sort();
+        assert_eq!(ret, vec![42, 69]);
+    }
+
+    #[test]
+    fn test_set_remove_removes_the_member() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("set_remove");
+        redis
+            .set_add::<MemberTable>(&key, &42)
+            .expect("Failed to add to set");
+        let removed = redis
+            .set_remove::<MemberTable>(&key, &42)
+            .expect("Failed to remove from set");
+        assert!(removed);
+        let is_member = redis
+            .set_is_member::<MemberTable>(&key, &42)
+            .expect("Failed to check set membership");
+        assert!(!is_member);
+    }
+}
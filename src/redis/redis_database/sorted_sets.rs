@@ -0,0 +1,110 @@
+use lushus_storage::Table;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::redis::{
+    commands::Command, error::RedisError, execute_command::ExecuteCommand,
+    redis_database::RedisDatabase, redis_key::RedisKey, serialization::Serializer,
+};
+
+impl<S: Serializer> RedisDatabase<S> {
+    /// Returns `true` if the member is new; otherwise only its score is updated. Every
+    /// addition refreshes the TTL of the whole sorted set.
+    pub fn zset_add<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        member: &TableType::Value,
+        score: f64,
+    ) -> Result<bool, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let key = self.table_key::<TableType>(key);
+        let member = self.encode(&key, member)?;
+        let command = Command::zadd(key.clone(), member, score);
+        self.execute_with_expire(command, key)
+    }
+
+    /// Members ranked `start..=stop` by ascending score, where negative indices count
+    /// from the highest score.
+    pub fn zset_range<TableType>(
+        &self,
+        key: &TableType::Key,
+        start: isize,
+        stop: isize,
+    ) -> Result<Vec<(TableType::OwnedValue, f64)>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::OwnedValue: DeserializeOwned,
+    {
+        let key = self.table_key::<TableType>(key);
+        let command = Command::zrange(key.clone(), start, stop);
+        let data = self.execute_command::<Vec<(Vec<u8>, f64)>>(command)?;
+        data.into_iter()
+            .map(|(member, score)| Ok((self.decode(&key, member)?, score)))
+            .collect()
+    }
+
+    /// The zero-based position by ascending score, or `None` if the member is absent.
+    pub fn zset_rank<TableType>(
+        &self,
+        key: &TableType::Key,
+        member: &TableType::Value,
+    ) -> Result<Option<u64>, RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let key = self.table_key::<TableType>(key);
+        let member = self.encode(&key, member)?;
+        let command = Command::zrank(key, member);
+        self.execute_command(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use lushus_storage::Table;
+
+    use crate::redis::{
+        redis_database::RedisDatabase,
+        test_support::{unique_key, url},
+    };
+
+    struct LeaderboardTable {}
+
+    impl Table for LeaderboardTable {
+        type Key = String;
+        type OwnedKey = Self::Key;
+        type Value = String;
+        type OwnedValue = Self::Value;
+    }
+
+    #[test]
+    fn test_zset_range_orders_members_by_score() {
+        let ttl = Duration::from_secs(1);
+        let mut redis = RedisDatabase::new(&url(), ttl).expect("Unable to connect to Redis");
+        let key = unique_key("zset_range");
+        let alice = "alice".to_string();
+        let bob = "bob".to_string();
+        redis
+            .zset_add::<LeaderboardTable>(&key, &alice, 69.0)
+            .expect("Failed to add to sorted set");
+        redis
+            .zset_add::<LeaderboardTable>(&key, &bob, 42.0)
+            .expect("Failed to add to sorted set");
+        let ret = redis
+            .zset_range::<LeaderboardTable>(&key, 0, -1)
+            .expect("Failed to get sorted set range");
+        assert_eq!(ret, vec![(bob, 42.0), (alice.clone(), 69.0)]);
+        let rank = redis
+            .zset_rank::<LeaderboardTable>(&key, &alice)
+            .expect("Failed to get sorted set rank");
+        assert_eq!(rank, Some(1));
+    }
+}
@@ -0,0 +1,86 @@
+use std::str::FromStr;
+
+use crate::redis::error::RedisError;
+
+/// How a table key is written to, and read back from, Redis. Unlike a plain `ToString`
+/// bound, the conversion must round-trip, so iterating over keys yields typed keys.
+///
+/// Any type that implements both `ToString` and `FromStr` is a `RedisKey`. Distinct keys
+/// must format to distinct strings, or they will overwrite each other. Composite keys
+/// control their encoding by implementing `RedisKey` themselves, escaping the separator
+/// in each component so that `("a", "b:c")` and `("a:b", "c")` stay apart.
+pub trait RedisKey {
+    fn to_redis_key(&self) -> String;
+
+    fn from_redis_key(key: &str) -> Result<Self, RedisError>
+    where
+        Self: Sized;
+}
+
+impl<T> RedisKey for T
+where
+    T: ToString + FromStr,
+    T::Err: ToString,
+{
+    fn to_redis_key(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_redis_key(key: &str) -> Result<Self, RedisError> {
+        key.parse()
+            .map_err(|e: T::Err| RedisError::DeserializeError(key.to_string(), e.to_string()))
+    }
+}
+
+/// Lets tables use `str` keys, which can be written but not iterated.
+impl RedisKey for str {
+    fn to_redis_key(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RedisKey;
+    use crate::redis::error::RedisError;
+
+    #[test]
+    fn test_keys_round_trip() {
+        let key = 42u64.to_redis_key();
+        assert_eq!(key, "42");
+        assert_eq!(u64::from_redis_key(&key).expect("Failed to parse key"), 42);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Pair(String, String);
+
+    impl RedisKey for Pair {
+        fn to_redis_key(&self) -> String {
+            let escape = |part: &str| part.replace('%', "%25").replace(':', "%3A");
+            format!("{}:{}", escape(&self.0), escape(&self.1))
+        }
+
+        fn from_redis_key(key: &str) -> Result<Self, RedisError> {
+            let unescape = |part: &str| part.replace("%3A", ":").replace("%25", "%");
+            let (first, second) = key.split_once(':').ok_or_else(|| {
+                RedisError::DeserializeError(key.to_string(), "missing separator".to_string())
+            })?;
+            Ok(Pair(unescape(first), unescape(second)))
+        }
+    }
+
+    #[test]
+    fn test_composite_keys_do_not_collide() {
+        let first = Pair("a".to_string(), "b:c".to_string());
+        let second = Pair("a:b".to_string(), "c".to_string());
+        assert_ne!(first.to_redis_key(), second.to_redis_key());
+        let ret = Pair::from_redis_key(&first.to_redis_key()).expect("Failed to parse key");
+        assert_eq!(ret, first);
+    }
+
+    #[test]
+    fn test_from_redis_key_reports_the_key() {
+        let ret = u64::from_redis_key("abc");
+        assert!(matches!(ret, Err(RedisError::DeserializeError(key, _)) if key == "abc"));
+    }
+}
@@ -0,0 +1,44 @@
+/// The type of value held at a key, as reported by `TYPE`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RedisType {
+    String,
+    List,
+    Set,
+    ZSet,
+    Hash,
+    Stream,
+    /// A type added by a module, such as `ReJSON-RL`.
+    Other(String),
+}
+
+impl RedisType {
+    /// Parses a `TYPE` reply, which is `none` for a missing key.
+    pub fn from_reply(reply: &str) -> Option<Self> {
+        let redis_type = match reply {
+            "none" => return None,
+            "string" => RedisType::String,
+            "list" => RedisType::List,
+            "set" => RedisType::Set,
+            "zset" => RedisType::ZSet,
+            "hash" => RedisType::Hash,
+            "stream" => RedisType::Stream,
+            other => RedisType::Other(other.to_string()),
+        };
+        Some(redis_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RedisType;
+
+    #[test]
+    fn test_from_reply_maps_the_type_names() {
+        assert_eq!(RedisType::from_reply("none"), None);
+        assert_eq!(RedisType::from_reply("zset"), Some(RedisType::ZSet));
+        assert_eq!(
+            RedisType::from_reply("ReJSON-RL"),
+            Some(RedisType::Other("ReJSON-RL".to_string()))
+        );
+    }
+}
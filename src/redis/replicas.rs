@@ -0,0 +1,57 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use r2d2::Pool;
+
+use crate::redis::{
+    connection_manager::{self, RedisConnectionManager},
+    redis_database::DEFAULT_POOL_SIZE,
+};
+
+/// How long a read waits for a replica connection before falling back to the write
+/// endpoint.
+pub const REPLICA_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Read endpoints, handed out in turn. Clones share the same rotation.
+#[derive(Clone, Debug, Default)]
+pub struct Replicas {
+    pools: Vec<Pool<RedisConnectionManager>>,
+    next: Arc<AtomicUsize>,
+}
+
+impl Replicas {
+    pub fn new(managers: Vec<RedisConnectionManager>) -> Self {
+        let pools = managers
+            .into_iter()
+            .map(|manager| {
+                Pool::builder()
+                    .max_size(DEFAULT_POOL_SIZE)
+                    .connection_timeout(REPLICA_TIMEOUT)
+                    .test_on_check_out(manager.test_on_check_out())
+                    .build_unchecked(manager)
+            })
+            .collect();
+        Self {
+            pools,
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// `None` when there are no replicas.
+    pub fn next(&self) -> Option<&Pool<RedisConnectionManager>> {
+        if self.pools.is_empty() {
+            return None;
+        }
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.pools.len();
+        self.pools.get(index)
+    }
+
+    pub fn drain(&self) {
+        self.pools.iter().for_each(connection_manager::drain);
+    }
+}
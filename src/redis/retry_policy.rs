@@ -0,0 +1,92 @@
+use std::{thread, time::Duration};
+
+use crate::redis::error::RedisError;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// `max_attempts` counts the initial attempt, so `1` disables retrying.
+    pub fn new(max_attempts: u32, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay,
+            max_delay,
+        }
+    }
+
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    pub(crate) fn run<T, F>(&self, mut operation: F) -> Result<T, RedisError>
+    where
+        F: FnMut() -> Result<T, RedisError>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation() {
+                Err(error) if error.is_retryable() => {
+                    if attempt >= self.max_attempts {
+                        return Err(RedisError::RetryError(attempt, Box::new(error)));
+                    }
+                    thread::sleep(self.delay(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RetryPolicy;
+    use crate::redis::error::RedisError;
+
+    #[test]
+    fn test_delay_grows_exponentially_up_to_the_max_delay() {
+        let policy = RetryPolicy::new(5, Duration::from_millis(10), Duration::from_millis(50));
+        assert_eq!(policy.delay(1), Duration::from_millis(10));
+        assert_eq!(policy.delay(2), Duration::from_millis(20));
+        assert_eq!(policy.delay(3), Duration::from_millis(40));
+        assert_eq!(policy.delay(4), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_run_retries_connection_errors_and_reports_the_attempts() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(1), Duration::from_millis(1));
+        let mut calls = 0;
+        let ret = policy.run::<(), _>(|| {
+            calls += 1;
+            Err(RedisError::ConnectionError(redis::RedisError::from((
+                redis::ErrorKind::IoError,
+                "dropped",
+            ))))
+        });
+        assert_eq!(calls, 3);
+        assert!(matches!(ret, Err(RedisError::RetryError(3, _))));
+    }
+
+    #[test]
+    fn test_run_does_not_retry_serialization_errors() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(1), Duration::from_millis(1));
+        let mut calls = 0;
+        let ret = policy.run::<(), _>(|| {
+            calls += 1;
+            Err(RedisError::SerializeError(
+                "key".to_string(),
+                "bad".to_string(),
+            ))
+        });
+        assert_eq!(calls, 1);
+        assert!(matches!(ret, Err(RedisError::SerializeError(_, _))));
+    }
+}
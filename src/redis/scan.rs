@@ -0,0 +1,88 @@
+use crate::redis::{commands::Command, error::RedisError, execute_command::ExecuteCommand};
+
+pub const SCAN_COUNT: usize = 100;
+
+/// Walks the items of a cursor-based command such as `SCAN`, fetching the next page only
+/// once the current one is used up.
+pub struct Scan<'a, E, T = String> {
+    executor: &'a E,
+    command: Box<dyn Fn(u64) -> Command + 'a>,
+    cursor: Option<u64>,
+    page: std::vec::IntoIter<T>,
+}
+
+impl<'a, E: ExecuteCommand> Scan<'a, E> {
+    /// Walks the keys matching `pattern`. `count` is a hint for how many keys Redis
+    /// examines per page.
+    pub fn new(executor: &'a E, pattern: String, count: usize) -> Self {
+        Self::with_command(executor, move |cursor| {
+            Command::scan(cursor, pattern.clone(), count)
+        })
+    }
+}
+
+impl<'a, E: ExecuteCommand, T: redis::FromRedisValue> Scan<'a, E, T> {
+    /// `command` builds the command for the page at a cursor.
+    pub fn with_command<C>(executor: &'a E, command: C) -> Self
+    where
+        C: Fn(u64) -> Command + 'a,
+    {
+        Self {
+            executor,
+            command: Box::new(command),
+            cursor: Some(0),
+            page: Vec::new().into_iter(),
+        }
+    }
+
+    pub fn fetch(&mut self) -> Result<(), RedisError> {
+        let Some(cursor) = self.cursor else {
+            return Ok(());
+        };
+        let command = (self.command)(cursor);
+        let result = self.executor.execute_command::<(u64, Vec<T>)>(command);
+        // Stop after an error rather than retrying the same page forever.
+        self.cursor = None;
+        let (next, keys) = result?;
+        self.cursor = (next != 0).then_some(next);
+        self.page = keys.into_iter();
+        Ok(())
+    }
+}
+
+impl<E: ExecuteCommand, T: redis::FromRedisValue> Iterator for Scan<'_, E, T> {
+    type Item = Result<T, RedisError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(key) = self.page.next() {
+                return Some(Ok(key));
+            }
+            self.cursor?;
+            if let Err(e) = self.fetch() {
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+pub fn escape_pattern(key: &str) -> String {
+    let mut pattern = String::with_capacity(key.len());
+    for c in key.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::escape_pattern;
+
+    #[test]
+    fn test_escape_pattern_escapes_glob_characters() {
+        assert_eq!(escape_pattern("a*b?[c]\\d"), "a\\*b\\?\\[c\\]\\\\d");
+    }
+}
@@ -0,0 +1,145 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::redis::error::RedisError;
+
+/// Converts values to and from the bytes stored in Redis.
+pub trait Serializer {
+    type Error: std::error::Error;
+
+    fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, Self::Error>;
+
+    fn deserialize<T: DeserializeOwned>(&self, data: &[u8]) -> Result<T, Self::Error>;
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct JsonSerializer;
+
+impl Serializer for JsonSerializer {
+    type Error = serde_json::Error;
+
+    fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+        serde_json::to_vec(value)
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, data: &[u8]) -> Result<T, Self::Error> {
+        serde_json::from_slice(data)
+    }
+}
+
+/// Writes indented JSON, which is easier to read from `redis-cli`. Values written by
+/// `JsonSerializer` are read back just the same.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PrettyJsonSerializer;
+
+impl Serializer for PrettyJsonSerializer {
+    type Error = serde_json::Error;
+
+    fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+        serde_json::to_vec_pretty(value)
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, data: &[u8]) -> Result<T, Self::Error> {
+        serde_json::from_slice(data)
+    }
+}
+
+pub fn serialize_value<S: Serializer, T: Serialize + ?Sized>(
+    serializer: &S,
+    key: &str,
+    value: &T,
+) -> Result<Vec<u8>, RedisError> {
+    serializer
+        .serialize(value)
+        .map_err(|e| RedisError::SerializeError(key.to_string(), e.to_string()))
+}
+
+pub fn deserialize_value<S: Serializer, T: DeserializeOwned>(
+    serializer: &S,
+    key: &str,
+    data: Option<Vec<u8>>,
+) -> Result<Option<T>, RedisError> {
+    data.map(|v| deserialize_bytes(serializer, key, v))
+        .transpose()
+}
+
+pub fn deserialize_bytes<S: Serializer, T: DeserializeOwned>(
+    serializer: &S,
+    key: &str,
+    data: Vec<u8>,
+) -> Result<T, RedisError> {
+    serializer
+        .deserialize::<T>(&data)
+        .map_err(|e| RedisError::DeserializeError(key.to_string(), e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use super::{
+        deserialize_value, serialize_value, JsonSerializer, PrettyJsonSerializer, Serializer,
+    };
+    use crate::redis::error::RedisError;
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Foo {
+        bar: u64,
+    }
+
+    #[test]
+    fn test_json_serializer_round_trips_the_value() {
+        let foo = Foo { bar: 42 };
+        let data = serialize_value(&JsonSerializer, "key", &foo).expect("Failed to serialize");
+        assert_eq!(data, br#"{"bar":42}"#);
+        let ret = deserialize_value::<_, Foo>(&JsonSerializer, "key", Some(data))
+            .expect("Failed to deserialize");
+        assert_eq!(ret, Some(foo));
+    }
+
+    #[test]
+    fn test_pretty_json_serializer_reads_compact_json() {
+        let foo = Foo { bar: 42 };
+        let data =
+            serialize_value(&PrettyJsonSerializer, "key", &foo).expect("Failed to serialize");
+        assert_eq!(data, b"{\n  \"bar\": 42\n}");
+        let compact = br#"{"bar":42}"#.to_vec();
+        let ret = deserialize_value::<_, Foo>(&PrettyJsonSerializer, "key", Some(compact))
+            .expect("Failed to deserialize");
+        assert_eq!(ret, Some(foo));
+    }
+
+    /// JSON behind a version byte, standing in for binary formats such as MessagePack
+    /// whose output can start with any byte.
+    struct VersionedSerializer;
+
+    impl Serializer for VersionedSerializer {
+        type Error = serde_json::Error;
+
+        fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            let mut data = vec![0x01];
+            data.extend(serde_json::to_vec(value)?);
+            Ok(data)
+        }
+
+        fn deserialize<T: DeserializeOwned>(&self, data: &[u8]) -> Result<T, Self::Error> {
+            serde_json::from_slice(&data[1..])
+        }
+    }
+
+    #[test]
+    fn test_values_starting_with_any_byte_round_trip() {
+        let foo = Foo { bar: 42 };
+        let data = serialize_value(&VersionedSerializer, "key", &foo).expect("Failed to serialize");
+        assert_eq!(data[0], 0x01);
+        let ret = deserialize_value::<_, Foo>(&VersionedSerializer, "key", Some(data))
+            .expect("Failed to deserialize");
+        assert_eq!(ret, Some(foo));
+    }
+
+    #[test]
+    fn test_deserialize_value_reports_the_key() {
+        let data = b"not json".to_vec();
+        let ret = deserialize_value::<_, Foo>(&JsonSerializer, "key", Some(data));
+        assert!(matches!(ret, Err(RedisError::DeserializeError(key, _)) if key == "key"));
+    }
+}
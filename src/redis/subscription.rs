@@ -0,0 +1,35 @@
+use redis::{Connection, Msg};
+
+use crate::redis::{connection_manager::query_error, error::RedisError};
+
+/// Yields message payloads from a channel, blocking until the next one arrives.
+pub struct Subscription {
+    connection: Connection,
+}
+
+impl Subscription {
+    pub(crate) fn new(mut connection: Connection, channel: &str) -> Result<Self, RedisError> {
+        redis::cmd("SUBSCRIBE")
+            .arg(channel)
+            .query::<()>(&mut connection)
+            .map_err(query_error)?;
+        Ok(Self { connection })
+    }
+}
+
+impl Iterator for Subscription {
+    type Item = Result<String, RedisError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let value = match self.connection.recv_response() {
+                Ok(value) => value,
+                Err(e) => return Some(Err(query_error(e))),
+            };
+            // Skip replies that are not messages, such as subscription confirmations.
+            if let Some(message) = Msg::from_value(&value) {
+                return Some(message.get_payload().map_err(query_error));
+            }
+        }
+    }
+}
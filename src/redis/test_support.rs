@@ -0,0 +1,28 @@
+use std::{
+    env, process,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Set `REDIS_URL` to run the tests against a server other than the local default.
+pub fn url() -> String {
+    env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".to_string())
+}
+
+/// Set `REDIS_SOCKET` to the path of the server's Unix domain socket.
+#[cfg(unix)]
+pub fn socket_path() -> String {
+    env::var("REDIS_SOCKET").unwrap_or_else(|_| "/tmp/redis.sock".to_string())
+}
+
+/// Tests run in parallel against the same server, so each key is made unique to the
+/// test run and call site rather than shared through a fixed name.
+pub fn unique_key(name: &str) -> String {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    let id = NEXT.fetch_add(1, Ordering::Relaxed);
+    format!("{name}:{}:{nanos}:{id}", process::id())
+}
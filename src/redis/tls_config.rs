@@ -0,0 +1,62 @@
+use std::{fs, path::Path};
+
+use redis::{ClientTlsConfig, TlsCertificates};
+
+use crate::redis::error::RedisError;
+
+/// Certificates used to verify the server, and optionally to authenticate the client.
+///
+/// Without a root certificate the system trust store is used.
+#[derive(Clone, Default)]
+pub struct TlsConfig {
+    root_cert: Option<Vec<u8>>,
+    client_tls: Option<ClientTlsConfig>,
+}
+
+impl TlsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn root_cert_pem(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_cert = Some(pem.into());
+        self
+    }
+
+    pub fn root_cert_file(self, path: impl AsRef<Path>) -> Result<Self, RedisError> {
+        let pem = read_pem(path.as_ref())?;
+        Ok(self.root_cert_pem(pem))
+    }
+
+    /// Enables mutual TLS with the given PEM encoded certificate and private key.
+    pub fn client_cert_pem(mut self, cert: impl Into<Vec<u8>>, key: impl Into<Vec<u8>>) -> Self {
+        self.client_tls = Some(ClientTlsConfig {
+            client_cert: cert.into(),
+            client_key: key.into(),
+        });
+        self
+    }
+
+    pub fn client_cert_files(
+        self,
+        cert_path: impl AsRef<Path>,
+        key_path: impl AsRef<Path>,
+    ) -> Result<Self, RedisError> {
+        let cert = read_pem(cert_path.as_ref())?;
+        let key = read_pem(key_path.as_ref())?;
+        Ok(self.client_cert_pem(cert, key))
+    }
+}
+
+impl From<TlsConfig> for TlsCertificates {
+    fn from(config: TlsConfig) -> Self {
+        TlsCertificates {
+            client_tls: config.client_tls,
+            root_cert: config.root_cert,
+        }
+    }
+}
+
+fn read_pem(path: &Path) -> Result<Vec<u8>, RedisError> {
+    fs::read(path).map_err(|e| RedisError::TlsError(format!("{}: {e}", path.display())))
+}
@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use lushus_storage::Table;
+use serde::Serialize;
+
+use crate::redis::{
+    commands::Command, error::RedisError, redis_database::RedisDatabase, redis_key::RedisKey,
+    serialization::Serializer,
+};
+
+/// Commands queued here are only sent once the transaction closure returns.
+pub struct Transaction<'a, S> {
+    database: &'a RedisDatabase<S>,
+    commands: Vec<Command>,
+}
+
+impl<'a, S: Serializer> Transaction<'a, S> {
+    pub(crate) fn new(database: &'a RedisDatabase<S>) -> Self {
+        Self {
+            database,
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn insert<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+    ) -> Result<(), RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let ttl = self.database.default_ttl();
+        self.push_set::<TableType>(key, value, ttl)
+    }
+
+    pub fn insert_with_ttl<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+        ttl: Duration,
+    ) -> Result<(), RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        self.push_set::<TableType>(key, value, Some(ttl))
+    }
+
+    fn push_set<TableType>(
+        &mut self,
+        key: &TableType::Key,
+        value: &TableType::Value,
+        ttl: Option<Duration>,
+    ) -> Result<(), RedisError>
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+        TableType::Value: Serialize,
+    {
+        let key = self.database.table_key::<TableType>(key);
+        let value = self.database.encode(&key, value)?;
+        self.commands.push(Command::set(key, value, ttl));
+        Ok(())
+    }
+
+    pub fn remove<TableType>(&mut self, key: &TableType::Key)
+    where
+        TableType: Table,
+        TableType::Key: RedisKey,
+    {
+        let key = self.database.table_key::<TableType>(key);
+        self.commands.push(Command::delete(key));
+    }
+
+    pub(crate) fn into_commands(self) -> Vec<Command> {
+        self.commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use lushus_storage::Table;
+
+    use super::Transaction;
+    use crate::redis::redis_database::RedisDatabase;
+
+    #[derive(Copy, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Foo {
+        bar: u64,
+    }
+
+    struct FooTable {}
+
+    impl Table for FooTable {
+        type Key = String;
+        type OwnedKey = Self::Key;
+        type Value = Foo;
+        type OwnedValue = Self::Value;
+    }
+
+    #[test]
+    fn test_transaction_buffers_every_command() {
+        let ttl = Duration::from_secs(1);
+        let redis = RedisDatabase::new("redis://localhost:6379", ttl).expect("Invalid Redis URL");
+        let mut transaction = Transaction::new(&redis);
+        let key = "key".to_string();
+        transaction
+            .insert::<FooTable>(&key, &Foo { bar: 42 })
+            .expect("Failed to serialize");
+        transaction.remove::<FooTable>(&key);
+        assert_eq!(transaction.into_commands().len(), 2);
+    }
+}
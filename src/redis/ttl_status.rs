@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TtlStatus {
+    Expires(Duration),
+    NoExpiry,
+    Missing,
+}
+
+impl TtlStatus {
+    /// Parses a `TTL` reply, which is -1 for a key without an expiry and -2 for a missing key.
+    pub fn from_secs(reply: i64) -> Self {
+        Self::from_reply(reply, Duration::from_secs)
+    }
+
+    /// Parses a `PTTL` reply, which uses the same special values as `TTL`.
+    pub fn from_millis(reply: i64) -> Self {
+        Self::from_reply(reply, Duration::from_millis)
+    }
+
+    fn from_reply(reply: i64, duration: fn(u64) -> Duration) -> Self {
+        match reply {
+            -2 => TtlStatus::Missing,
+            -1 => TtlStatus::NoExpiry,
+            reply => TtlStatus::Expires(duration(u64::try_from(reply).unwrap_or(0))),
+        }
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            TtlStatus::Expires(duration) => Some(*duration),
+            TtlStatus::NoExpiry | TtlStatus::Missing => None,
+        }
+    }
+}
+
+/// `StorageTemp::ttl` can only return a `Duration`, so a key without an expiry reports
+/// `Duration::MAX` and a missing key reports zero.
+impl From<TtlStatus> for Duration {
+    fn from(status: TtlStatus) -> Self {
+        match status {
+            TtlStatus::Expires(duration) => duration,
+            TtlStatus::NoExpiry => Duration::MAX,
+            TtlStatus::Missing => Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::TtlStatus;
+
+    #[test]
+    fn test_from_secs_maps_the_special_replies() {
+        assert_eq!(TtlStatus::from_secs(-2), TtlStatus::Missing);
+        assert_eq!(TtlStatus::from_secs(-1), TtlStatus::NoExpiry);
+        assert_eq!(
+            TtlStatus::from_secs(5),
+            TtlStatus::Expires(Duration::from_secs(5))
+        );
+    }
+
+    #[test]
+    fn test_from_millis_keeps_sub_second_precision() {
+        assert_eq!(
+            TtlStatus::from_millis(1500),
+            TtlStatus::Expires(Duration::from_millis(1500))
+        );
+    }
+}